@@ -0,0 +1,13 @@
+#!/bin/bash
+# Compares the generated `apply` with `dispatch=chain` and `dispatch=switch` for a 40 variant enum
+set -e
+cd "$(dirname "$0")/.."
+cargo build --release
+OUT=./target/bench-dispatch
+mkdir -p $OUT
+for DISPATCH in chain switch
+do
+    ./target/release/enum-ts generate --target=js --dispatch=$DISPATCH < ./benches/dispatch/message.ts > $OUT/$DISPATCH.mjs
+done
+cp ./benches/dispatch/bench.mjs $OUT/bench.mjs
+node $OUT/bench.mjs
@@ -0,0 +1,35 @@
+// Times `Message.match` for the `dispatch=chain` and `dispatch=switch` outputs.
+// Run through `benches/dispatch.sh`, which generates `chain.mjs` and `switch.mjs` next to this file.
+import * as chain from "./chain.mjs";
+import * as switched from "./switch.mjs";
+
+const ITERATIONS = 200;
+
+function bench(name, mod) {
+  const creators = Object.keys(mod).filter(
+    (key) => key !== "Message" && !key.startsWith("is")
+  );
+  const values = [];
+  for (let i = 0; i < 10000; i++) {
+    values.push(mod[creators[i % creators.length]]({ id: i }));
+  }
+  const fns = {};
+  creators.forEach((variant, idx) => {
+    fns[variant] = (content) => content.id + idx;
+  });
+  const apply = mod.Message.apply(fns);
+
+  let total = 0;
+  // warm up
+  for (const value of values) total += apply(value);
+  const start = performance.now();
+  for (let i = 0; i < ITERATIONS; i++) {
+    for (const value of values) total += apply(value);
+  }
+  const elapsed = performance.now() - start;
+  const perCall = (elapsed * 1e6) / (ITERATIONS * values.length);
+  console.log(`${name}: ${elapsed.toFixed(1)}ms (${perCall.toFixed(1)}ns/call, checksum ${total})`);
+}
+
+bench("dispatch=chain ", chain);
+bench("dispatch=switch", switched);
@@ -0,0 +1,45 @@
+import { Enum } from "../../enum";
+
+// 40 variants, similar to a chat protocol message enum
+export type Message = Enum<{
+  Connect: { id: number };
+  Disconnect: { id: number };
+  Ping: { id: number };
+  Pong: { id: number };
+  Subscribe: { id: number };
+  Unsubscribe: { id: number };
+  Publish: { id: number };
+  Ack: { id: number };
+  Nack: { id: number };
+  Retry: { id: number };
+  Join: { id: number };
+  Leave: { id: number };
+  Invite: { id: number };
+  Kick: { id: number };
+  Ban: { id: number };
+  Mute: { id: number };
+  Unmute: { id: number };
+  Typing: { id: number };
+  StopTyping: { id: number };
+  Read: { id: number };
+  Edit: { id: number };
+  Delete: { id: number };
+  React: { id: number };
+  Unreact: { id: number };
+  Pin: { id: number };
+  Unpin: { id: number };
+  Upload: { id: number };
+  Download: { id: number };
+  Progress: { id: number };
+  Cancel: { id: number };
+  Presence: { id: number };
+  Status: { id: number };
+  Profile: { id: number };
+  Settings: { id: number };
+  Search: { id: number };
+  Results: { id: number };
+  Error: { id: number };
+  Warning: { id: number };
+  Notice: { id: number };
+  Close: { id: number };
+}>;
@@ -0,0 +1,48 @@
+#!/bin/bash
+# Times `enum-ts check` over a synthetic tree of mostly enum-free files, a few declaring enums,
+# and a few large generated bundles. Set BASELINE to another enum-ts binary to compare with it.
+#   FILES=20000 BASELINE=~/enum-ts-0.2.6 ./benches/large_tree.sh
+set -e
+cd "$(dirname "$0")/.."
+cargo build --release
+BIN=$PWD/target/release/enum-ts
+FILES=${FILES:-20000}
+# outside of the repository, whose .gitignore would skip the whole tree
+TREE=${TMPDIR:-/tmp}/enum-ts-bench-large-tree
+rm -rf "$TREE"
+mkdir -p "$TREE"
+
+# a module of about 4KB without enums
+BODY=$(for ((LINE = 0; LINE < 40; LINE++)); do
+    printf '\nexport function handle%d(value: number): number {\n    return helper(value) * %d;\n}\n' $LINE $LINE
+done)
+
+echo "Generating $FILES files in $TREE"
+for ((IDX = 0; IDX < FILES; IDX++)); do
+    DIR=$TREE/pkg$((IDX % 100))/src
+    mkdir -p "$DIR"
+    if ((IDX % 50 == 0)); then
+        printf '// declares an enum\nexport type Shape%d = Enum<{\n    Circle: { radius: number };\n    Square: { side: number };\n}>;\n' $IDX > "$DIR/shape$IDX.ts"
+    else
+        printf 'import { helper } from "./helper";\n%s\n' "$BODY" > "$DIR/module$IDX.ts"
+    fi
+done
+for IDX in 0 1 2 3; do
+    yes 'export const data = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];' | head -c 8000000 > "$TREE/pkg$IDX/bundle.ts"
+done
+(cd "$TREE" && "$BIN" write --no-cache > /dev/null 2>&1)
+
+cd "$TREE"
+run() {
+    if command -v hyperfine > /dev/null; then
+        hyperfine --warmup 1 --ignore-failure "$*"
+    else
+        echo "$*"
+        time ("$@" > /dev/null 2>&1 || true)
+    fi
+}
+run "$BIN" check --no-cache
+run "$BIN" check
+if [ -n "$BASELINE" ]; then
+    run "$BASELINE" check
+fi
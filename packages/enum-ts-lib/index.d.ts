@@ -12,3 +12,13 @@
 export type Enum<T extends { [Variant: string]: any }> = {
   [P in keyof T]: Record<P, T[P]>;
 }[keyof T];
+
+/**
+ * Thrown by the `apply` and `match` functions generated with `unreachable=throw`
+ * when given a value which is not one of the enum's variants
+ */
+export declare class UnexpectedVariantError extends Error {
+  enumName: string;
+  value: unknown;
+  constructor(enumName: string, value: unknown);
+}
@@ -1 +1,16 @@
-// This package only exports type definitions
+"use strict";
+Object.defineProperty(exports, "__esModule", { value: true });
+
+/**
+ * Thrown by the `apply` and `match` functions generated with `unreachable=throw`
+ * when given a value which is not one of the enum's variants
+ */
+class UnexpectedVariantError extends Error {
+  constructor(enumName, value) {
+    super("Unexpected Enum variant for " + enumName);
+    this.name = "UnexpectedVariantError";
+    this.enumName = enumName;
+    this.value = value;
+  }
+}
+exports.UnexpectedVariantError = UnexpectedVariantError;
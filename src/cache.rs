@@ -0,0 +1,345 @@
+//! A record of the files which were up to date after a run, so that later runs with the same
+//! options can skip them. A file is skipped when its modified time and size are unchanged, or
+//! when they changed but its contents hash the same.
+use crate::*;
+
+use serde_json::{json, Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, Metadata};
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Modified times this close to when an entry is recorded can't be trusted, as the file could
+/// still change within the file system's timestamp resolution
+const RACY_MODIFIED: Duration = Duration::from_secs(2);
+
+/// Where the cache is kept when not given, in `node_modules/.cache` if there is a `node_modules`
+pub fn default_location(base_dir: &Path) -> PathBuf {
+    let node_modules = base_dir.join("node_modules");
+    if node_modules.is_dir() {
+        node_modules.join(".cache/enum-ts/cache.json")
+    } else {
+        base_dir.join(".enum-ts-cache")
+    }
+}
+
+/// Identifies everything other than a file's contents which its generated code depends on
+pub fn context(options: &Options, js: bool) -> String {
+    let mut hasher = DefaultHasher::new();
+    VERSION.hash(&mut hasher);
+    CODE_GEN_VERSION.hash(&mut hasher);
+    options.hash(&mut hasher);
+    js.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+pub fn hash_contents(contents: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+/// The modified time in nanoseconds and the size of a file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stamp {
+    modified: u64,
+    size: u64,
+}
+
+impl Stamp {
+    pub fn of(metadata: &Metadata) -> Stamp {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since_epoch| since_epoch.as_nanos() as u64);
+        Stamp {
+            modified,
+            size: metadata.len(),
+        }
+    }
+
+    /// The stamp of the file at `path`, or `None` if it doesn't exist
+    fn read(path: &Path) -> Option<Stamp> {
+        fs::metadata(path).ok().map(|metadata| Stamp::of(&metadata))
+    }
+
+    /// A stamp which only matches by content hash, if the file was modified too recently
+    fn trusted(self, now: SystemTime) -> Stamp {
+        let modified = UNIX_EPOCH + Duration::from_nanos(self.modified);
+        match now.duration_since(modified) {
+            Ok(age) if age > RACY_MODIFIED => self,
+            _ => Stamp {
+                modified: 0,
+                size: self.size,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    stamp: Stamp,
+    hash: String,
+    enums: usize,
+    /// Stamps of the generated `.enum.js` and `.enum.d.ts` files, which must not have changed
+    outputs: Vec<Option<Stamp>>,
+}
+
+#[derive(Debug)]
+pub struct Cache {
+    location: PathBuf,
+    context: String,
+    entries: HashMap<PathBuf, Entry>,
+    /// The files looked up during this run, so entries for deleted files aren't saved
+    seen: HashSet<PathBuf>,
+}
+
+impl Cache {
+    /// Loads the cache at `location`, starting empty if it is missing, unreadable or was
+    /// recorded with a different `context`
+    pub fn load(location: PathBuf, context: String) -> Cache {
+        let entries = fs::read(&location)
+            .ok()
+            .and_then(|contents| serde_json::from_slice::<Value>(&contents).ok())
+            .filter(|cache| cache["context"] == context.as_str())
+            .and_then(|cache| {
+                let files = cache["files"].as_object()?;
+                Some(
+                    files
+                        .iter()
+                        .filter_map(|(path, entry)| {
+                            Some((PathBuf::from(path), entry_from_json(entry)?))
+                        })
+                        .collect(),
+                )
+            })
+            .unwrap_or_default();
+        Cache {
+            location,
+            context,
+            entries,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Whether `path` can be skipped because its stamp is unchanged since it was up to date,
+    /// returning the number of enums it declares
+    pub fn unchanged(&mut self, path: &Path, stamp: Stamp, outputs: &[PathBuf]) -> Option<usize> {
+        self.seen.insert(path.to_path_buf());
+        let entry = self.entries.get(path)?;
+        if entry.stamp != stamp || !outputs_unchanged(entry, outputs) {
+            return None;
+        }
+        Some(entry.enums)
+    }
+
+    /// Whether `path` can be skipped because its contents hash the same as when it was up to date,
+    /// returning the number of enums it declares. Its new `stamp` is recorded, so that it can be
+    /// skipped without hashing next time.
+    pub fn same_contents(
+        &mut self,
+        path: &Path,
+        stamp: Stamp,
+        hash: &str,
+        outputs: &[PathBuf],
+    ) -> Option<usize> {
+        let entry = self.entries.get_mut(path)?;
+        if entry.hash != hash || !outputs_unchanged(entry, outputs) {
+            return None;
+        }
+        entry.stamp = stamp.trusted(SystemTime::now());
+        Some(entry.enums)
+    }
+
+    /// Records that `path`, whose contents hash to `hash`, is up to date. The hash is empty for
+    /// files which have nothing to update, so were not hashed.
+    pub fn record(&mut self, path: &Path, hash: &str, enums: usize, outputs: &[PathBuf]) {
+        self.seen.insert(path.to_path_buf());
+        let now = SystemTime::now();
+        let stamp = match Stamp::read(path) {
+            Some(stamp) => stamp.trusted(now),
+            None => return,
+        };
+        let entry = Entry {
+            stamp,
+            hash: hash.to_string(),
+            enums,
+            outputs: outputs
+                .iter()
+                .map(|output| Stamp::read(output).map(|stamp| stamp.trusted(now)))
+                .collect(),
+        };
+        self.entries.insert(path.to_path_buf(), entry);
+    }
+
+    /// Forgets `path`, which is out of date or could not be read
+    pub fn forget(&mut self, path: &Path) {
+        self.entries.remove(path);
+    }
+
+    /// Writes the entries of the files seen during this run
+    pub fn save(&self) -> io::Result<()> {
+        let files: Map<String, Value> = self
+            .entries
+            .iter()
+            .filter(|(path, _)| self.seen.contains(*path))
+            .filter_map(|(path, entry)| Some((path.to_str()?.to_string(), entry_to_json(entry))))
+            .collect();
+        let cache = json!({ "context": self.context, "files": files });
+        if let Some(dir) = self.location.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomic(&self.location, &cache.to_string())
+    }
+}
+
+/// Whether the generated `.enum.js` and `.enum.d.ts` files are unchanged since the entry
+fn outputs_unchanged(entry: &Entry, outputs: &[PathBuf]) -> bool {
+    entry.outputs.len() == outputs.len()
+        && entry
+            .outputs
+            .iter()
+            .zip(outputs)
+            .all(|(stamp, output)| *stamp == Stamp::read(output))
+}
+
+/// `[modified, size]`, or `null` for a missing output
+fn stamp_to_json(stamp: &Option<Stamp>) -> Value {
+    match stamp {
+        Some(stamp) => json!([stamp.modified, stamp.size]),
+        None => Value::Null,
+    }
+}
+
+fn stamp_from_json(value: &Value) -> Option<Option<Stamp>> {
+    if value.is_null() {
+        return Some(None);
+    }
+    Some(Some(Stamp {
+        modified: value[0].as_u64()?,
+        size: value[1].as_u64()?,
+    }))
+}
+
+fn entry_to_json(entry: &Entry) -> Value {
+    json!({
+        "stamp": stamp_to_json(&Some(entry.stamp)),
+        "hash": entry.hash,
+        "enums": entry.enums,
+        "outputs": entry.outputs.iter().map(stamp_to_json).collect::<Vec<_>>(),
+    })
+}
+
+fn entry_from_json(value: &Value) -> Option<Entry> {
+    Some(Entry {
+        stamp: stamp_from_json(&value["stamp"])??,
+        hash: value["hash"].as_str()?.to_string(),
+        enums: value["enums"].as_u64()? as usize,
+        outputs: value["outputs"]
+            .as_array()?
+            .iter()
+            .map(stamp_from_json)
+            .collect::<Option<_>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_round_trip() {
+        let entry = Entry {
+            stamp: Stamp {
+                modified: 1_600_000_000_000_000_000,
+                size: 120,
+            },
+            hash: hash_contents(b"type A = 1;"),
+            enums: 2,
+            outputs: vec![
+                Some(Stamp {
+                    modified: 0,
+                    size: 3,
+                }),
+                None,
+            ],
+        };
+        assert_eq!(entry_from_json(&entry_to_json(&entry)), Some(entry));
+        assert_eq!(entry_from_json(&json!({ "hash": "a" })), None);
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("enum-ts-cache-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn same_contents_records_the_new_stamp() {
+        let dir = temp_dir("stamp");
+        let path = dir.join("a.ts");
+        fs::write(&path, "type A = 1;").unwrap();
+        let hash = hash_contents(b"type A = 1;");
+        let mut cache = Cache::load(dir.join("cache.json"), "context".to_string());
+        // just written, so only its contents can be trusted
+        cache.record(&path, &hash, 1, &[]);
+        let stamp = Stamp::read(&path).unwrap();
+        assert_eq!(cache.unchanged(&path, stamp, &[]), None);
+
+        let old = Stamp {
+            modified: stamp.modified - 60_000_000_000,
+            ..stamp
+        };
+        assert_eq!(cache.same_contents(&path, old, &hash, &[]), Some(1));
+        assert_eq!(cache.unchanged(&path, old, &[]), Some(1));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn save_drops_files_not_seen() {
+        let dir = temp_dir("prune");
+        let location = dir.join("cache.json");
+        let (kept, deleted) = (dir.join("kept.ts"), dir.join("deleted.ts"));
+        let mut cache = Cache::load(location.clone(), "context".to_string());
+        for path in [&kept, &deleted] {
+            fs::write(path, "").unwrap();
+            cache.record(path, &hash_contents(b""), 0, &[]);
+        }
+        cache.save().unwrap();
+        assert_eq!(
+            Cache::load(location.clone(), "context".to_string())
+                .entries
+                .len(),
+            2
+        );
+
+        fs::remove_file(&deleted).unwrap();
+        let mut cache = Cache::load(location.clone(), "context".to_string());
+        cache.unchanged(&kept, Stamp::read(&kept).unwrap(), &[]);
+        cache.save().unwrap();
+        let cache = Cache::load(location, "context".to_string());
+        assert_eq!(cache.entries.keys().collect::<Vec<_>>(), vec![&kept]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn recent_stamps_are_not_trusted() {
+        let now = SystemTime::now();
+        let since_epoch = |time: SystemTime| time.duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let recent = Stamp {
+            modified: since_epoch(now) as u64,
+            size: 1,
+        };
+        assert_eq!(recent.trusted(now).modified, 0);
+        let old = Stamp {
+            modified: since_epoch(now - Duration::from_secs(60)) as u64,
+            size: 1,
+        };
+        assert_eq!(old.trusted(now), old);
+    }
+}
@@ -1,35 +1,358 @@
 use crate::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
 
 mod apply_match;
 mod creators;
+mod equality;
 mod type_aliases;
 mod type_guards;
+mod unreachable;
 
 // if the enum generated type structure ever updates, then increment this
-pub const CODE_GEN_VERSION: usize = 6;
-pub fn generate(Parsed { enums, indent }: Parsed) -> String {
-    let mut code = String::new();
-    for ts_enum in enums {
-        let TSEnum { name, export, .. } = &ts_enum;
+pub const CODE_GEN_VERSION: usize = 11;
+
+/// Where `UnexpectedVariantError` is imported from for `unreachable=throw`
+const DEFAULT_ERROR_MODULE: &str = "enum-ts-lib";
+
+/// Code generation settings for a whole project, which can be set from the command line
+/// (e.g. `--equals`) or for a single enum with a `// enum-ts: equals` comment above it.
+#[derive(Debug, Default, Clone, Hash)]
+pub struct Options {
+    /// Generate `equals` and `hash` helpers in the enum's namespace
+    pub equals: bool,
+    /// How `apply`/`match` are grouped under the enum's name
+    pub members: Members,
+    /// What `apply` does when given a value which is not one of the variants
+    pub unreachable: Unreachable,
+    /// How `apply` finds the variant of a value
+    pub dispatch: Dispatch,
+    /// What the creator functions return
+    pub creators: Creators,
+    /// Generate constants like `Stoplight.Red` for the variants without contents
+    pub constants: bool,
+    /// Mark the variant key `readonly` in creator return types and type guards
+    pub readonly: bool,
+    /// `Object.freeze` the values made by creators and constants
+    pub freeze: bool,
+    /// How quoted variant names like `"not-found"` become identifiers for creators and guards
+    pub mangle: Mangle,
+}
 
-        let mut ns_src = Source::new(indent.clone());
-        type_aliases::generate(&ts_enum, &mut ns_src);
-        creators::generate(&ts_enum, &mut ns_src);
-        type_guards::generate(&ts_enum, &mut ns_src);
+/// How the `apply`/`match` functions are grouped under the enum's name
+#[derive(Clone, Copy, Debug, Default, PartialEq, Hash)]
+pub enum Members {
+    /// `namespace Result { export function match() {} }`
+    #[default]
+    Namespace,
+    /// `const Result = { match() {} } as const;` for `isolatedModules` and Babel
+    Object,
+    /// `function matchResult() {}`
+    Functions,
+}
 
-        ns_src.ln_push("");
-        // "export namespace Result {"
-        if *export {
-            ns_src.push("export ");
+impl Members {
+    /// JavaScript has no namespaces, and declarations for an object can be a namespace
+    fn for_dialect(self, dialect: Dialect) -> Members {
+        match (self, dialect) {
+            (Members::Functions, _) => Members::Functions,
+            (_, Dialect::JavaScript) => Members::Object,
+            (_, Dialect::Declarations) => Members::Namespace,
+            (members, Dialect::TypeScript) => members,
         }
-        ns_src.push("namespace ");
-        ns_src.push(&name);
-        ns_src.push(" {");
+    }
+
+    /// "Result.match" or "matchResult"
+    fn qualify(self, enum_name: &str, member: &str) -> String {
+        match self {
+            Members::Namespace | Members::Object => format!("{}.{}", enum_name, member),
+            Members::Functions => format!("{}{}", member, enum_name),
+        }
+    }
+}
+
+/// What `apply` does when given a value which is not one of the variants
+#[derive(Clone, Debug, Default, PartialEq, Hash)]
+pub enum Unreachable {
+    /// `console.assert(false, ...)` and return `undefined`
+    #[default]
+    Assert,
+    /// Throw an `UnexpectedVariantError` with the enum name and value, imported from a module
+    /// so that `instanceof` works across files
+    Throw(String),
+    /// Return the result of calling a function in scope with the enum name and value
+    Handler(String),
+}
+
+/// How `apply` finds the variant of a value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Hash)]
+pub enum Dispatch {
+    /// `"Ok" in item ? ... : "Err" in item ? ...` checks each variant in order
+    #[default]
+    Chain,
+    /// `for (const key in item) switch (key) { ... }` reads the key once,
+    /// which is faster for enums with many variants
+    Switch,
+}
+
+/// What the creator functions like `Ok(contents)` return
+#[derive(Clone, Copy, Debug, Default, PartialEq, Hash)]
+pub enum Creators {
+    /// `{ Ok: Ok<O> }`, only the variant
+    #[default]
+    Variant,
+    /// `Result<O, never>`, the whole enum with `never` for generics the variant does not use
+    Enum,
+}
+
+/// How quoted variant names which are not identifiers are named in creators and guards
+#[derive(Clone, Copy, Debug, Default, PartialEq, Hash)]
+pub enum Mangle {
+    /// `"not-found"` to `NotFound`, `"404"` to `_404`
+    #[default]
+    Pascal,
+    /// `"not-found"` to `not_found`, `"404"` to `_404`
+    Underscore,
+}
+
+impl Options {
+    /// Apply a single `key` or `key=value` setting
+    pub fn apply_directive(&mut self, directive: &str) -> Result<(), String> {
+        let (key, value) = match directive.find('=') {
+            Some(idx) => (directive[..idx].trim(), Some(directive[idx + 1..].trim())),
+            None => (directive.trim(), None),
+        };
+        match (key, value) {
+            ("equals", None) => self.equals = true,
+            ("constants", None) => self.constants = true,
+            ("readonly", None) => self.readonly = true,
+            ("freeze", None) => self.freeze = true,
+            ("mangle", Some("pascal")) => self.mangle = Mangle::Pascal,
+            ("mangle", Some("underscore")) => self.mangle = Mangle::Underscore,
+            ("members", Some("namespace")) => self.members = Members::Namespace,
+            ("members", Some("object")) => self.members = Members::Object,
+            ("members", Some("functions")) => self.members = Members::Functions,
+            ("dispatch", Some("chain")) => self.dispatch = Dispatch::Chain,
+            ("dispatch", Some("switch")) => self.dispatch = Dispatch::Switch,
+            ("creators", Some("variant")) => self.creators = Creators::Variant,
+            ("creators", Some("enum")) => self.creators = Creators::Enum,
+            ("unreachable", Some("assert")) => self.unreachable = Unreachable::Assert,
+            ("unreachable", Some("throw")) => {
+                self.unreachable = Unreachable::Throw(DEFAULT_ERROR_MODULE.to_string())
+            }
+            ("unreachable", Some(value)) if value.starts_with("throw:") => {
+                let module = value["throw:".len()..].trim();
+                if module.is_empty() || module.contains(['"', '\\']) {
+                    return Err(format!("Invalid enum-ts unreachable module: {:?}", module));
+                }
+                self.unreachable = Unreachable::Throw(module.to_string());
+            }
+            ("unreachable", Some(value)) if value.starts_with("handler:") => {
+                let handler = value["handler:".len()..].trim();
+                if handler.is_empty()
+                    || !handler
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || "_$.".contains(c))
+                {
+                    return Err(format!(
+                        "Invalid enum-ts unreachable handler: {:?}",
+                        handler
+                    ));
+                }
+                self.unreachable = Unreachable::Handler(handler.to_string());
+            }
+            _ => return Err(format!("Unknown enum-ts option: {:?}", directive)),
+        }
+        Ok(())
+    }
+
+    /// `"readonly "` or `""` before the variant key, as in `{ readonly Ok: Ok<O> }`
+    fn readonly_key(&self) -> &'static str {
+        if self.readonly {
+            "readonly "
+        } else {
+            ""
+        }
+    }
+
+    /// `"{ Ok: contents }"` or `"Object.freeze({ Ok: contents })"`
+    fn variant_value(&self, t_name: &str, contents: &str) -> String {
+        let object = format!("{{ {}: {} }}", variant_key(t_name), contents);
+        if self.freeze {
+            format!("Object.freeze({})", object)
+        } else {
+            object
+        }
+    }
+
+    /// Name of the variant's type alias and creator, `"Ok"` or `"NotFound"` for `"not-found"`
+    fn variant_ident<'a>(&self, t_name: &'a str) -> Cow<'a, str> {
+        if is_identifier(t_name) {
+            return Cow::Borrowed(t_name);
+        }
+        let mut ident = String::new();
+        // `"404"` to `_404`, and `"delete"` to `_delete` when it isn't capitalized
+        if !t_name.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+            || (self.mangle == Mangle::Underscore && RESERVED_WORDS.contains(&t_name))
+        {
+            ident.push('_');
+        }
+        match self.mangle {
+            Mangle::Pascal => {
+                for word in t_name.split(|c: char| !c.is_alphanumeric()) {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        ident.extend(first.to_uppercase());
+                        ident.extend(chars);
+                    }
+                }
+            }
+            Mangle::Underscore => ident.extend(t_name.chars().map(|c| {
+                if c.is_alphanumeric() || c == '$' {
+                    c
+                } else {
+                    '_'
+                }
+            })),
+        }
+        Cow::Owned(ident)
+    }
+
+    /// Project options with the enum's own directives applied on top
+    pub(crate) fn for_enum(&self, ts_enum: &TSEnum) -> Options {
+        let mut options = self.clone();
+        for directive in &ts_enum.directives {
+            // unknown directives are only warned about by `check_options`, so older versions
+            // don't fail on newer sources
+            let _ = options.apply_directive(directive);
+        }
+        options
+    }
+}
+
+/// Adds the errors which depend on the options each enum is generated with, like two variants
+/// which are named the same once mangled, and warnings for directives which could not be applied
+pub fn check_options(parsed: &mut Parsed, options: &Options) {
+    let mut error_module: Option<(&str, String)> = None;
+    for ts_enum in parsed.enums.iter() {
+        for directive in ts_enum.directives.iter() {
+            if let Err(err) = Options::default().apply_directive(directive) {
+                parsed
+                    .warnings
+                    .push(format!("enum {}: {}", ts_enum.name, err));
+            }
+        }
+        let options = options.for_enum(ts_enum);
+        if let Unreachable::Throw(module) = &options.unreachable {
+            match &error_module {
+                // one `UnexpectedVariantError` is imported for the whole file
+                Some((other, other_module)) if other_module != module => {
+                    parsed.errors.push(format!(
+                        "enum {}: `UnexpectedVariantError` is imported from `{}`, but from `{}` for enum {}",
+                        ts_enum.name, module, other_module, other
+                    ))
+                }
+                Some(_) => {}
+                None => error_module = Some((&ts_enum.name, module.clone())),
+            }
+        }
+        let mut idents = HashMap::new();
+        for (t_name, _) in ts_enum.variants.iter() {
+            let ident = options.variant_ident(t_name);
+            if let Some(other) = idents.insert(ident.clone(), t_name) {
+                parsed.errors.push(format!(
+                    "enum {}: variants `{}` and `{}` are both named `{}`",
+                    ts_enum.name, other, t_name, ident
+                ));
+            }
+        }
+    }
+}
+
+pub fn generate(parsed: Parsed) -> String {
+    generate_with(parsed, &Options::default())
+}
+
+pub fn generate_with(parsed: Parsed, options: &Options) -> String {
+    generate_dialect(parsed, options, Dialect::TypeScript)
+}
+
+/// ES module JavaScript for creators, type guards and matchers, typed by [`generate_declarations`]
+pub fn generate_js(parsed: Parsed, options: &Options) -> String {
+    generate_dialect(parsed, options, Dialect::JavaScript)
+}
+
+/// `.d.ts` typings for the JavaScript from [`generate_js`], including the enum types themselves
+pub fn generate_declarations(parsed: Parsed, options: &Options) -> String {
+    generate_dialect(parsed, options, Dialect::Declarations)
+}
+
+fn generate_dialect(
+    Parsed {
+        mut enums, indent, ..
+    }: Parsed,
+    options: &Options,
+    dialect: Dialect,
+) -> String {
+    if dialect != Dialect::TypeScript {
+        // separate modules are only useful if everything can be imported
+        for ts_enum in enums.iter_mut() {
+            ts_enum.export = true;
+        }
+    }
+    let enum_options: Vec<Options> = enums.iter().map(|e| options.for_enum(e)).collect();
+    let mut code = String::new();
+    for (ts_enum, options) in enums.iter().zip(enum_options.iter()) {
+        let TSEnum { name, export, .. } = ts_enum;
+        let members = options.members.for_dialect(dialect);
+
+        let mut ns_src = Source::new(indent.clone(), dialect, members);
+        type_aliases::generate(ts_enum, options, &mut ns_src);
+        creators::generate(ts_enum, options, &mut ns_src);
+        type_guards::generate(ts_enum, options, &mut ns_src);
 
         let mut nested_src = ns_src.new_with_same_settings();
-        apply_match::generate(&ts_enum, &mut nested_src);
-        ns_src.push_source_1(nested_src);
-        ns_src.ln_push("}");
+        if options.constants {
+            creators::generate_constants(ts_enum, options, &mut nested_src);
+        }
+        apply_match::generate(ts_enum, options, &mut nested_src);
+        if options.equals {
+            // nested enums with their own `equals` can be compared recursively
+            let comparable: Vec<(&TSEnum, Members)> = enums
+                .iter()
+                .zip(enum_options.iter())
+                .filter(|(_, options)| options.equals)
+                .map(|(other, options)| (other, options.members.for_dialect(dialect)))
+                .collect();
+            equality::generate(ts_enum, options, &comparable, &mut nested_src);
+        }
+
+        if members == Members::Functions {
+            ns_src.push_source(nested_src);
+        } else {
+            ns_src.ln_push_doc(ts_enum.doc.as_deref());
+            ns_src.ln_push("");
+            if *export {
+                ns_src.push("export ");
+            }
+            if members == Members::Namespace {
+                // "export namespace Result {"
+                ns_src.push("namespace ");
+                ns_src.push(name);
+                ns_src.push(" {");
+            } else {
+                // "export const Result = {"
+                ns_src.push("const ");
+                ns_src.push(name);
+                ns_src.push(" = {");
+            }
+            ns_src.push_source_1(nested_src);
+            match (members, dialect) {
+                (Members::Namespace, _) => ns_src.ln_push("}"),
+                (_, Dialect::JavaScript) => ns_src.ln_push("};"),
+                _ => ns_src.ln_push("} as const;"),
+            }
+        }
 
         if !code.is_empty() {
             code += "\n"
@@ -37,97 +360,531 @@ pub fn generate(Parsed { enums, indent }: Parsed) -> String {
         code.extend(ns_src.finish().drain(..));
     }
 
+    // the declarations of JavaScript which imports the error don't need it
+    let error_module = enum_options
+        .iter()
+        .find_map(|options| match &options.unreachable {
+            Unreachable::Throw(module) => Some(module),
+            _ => None,
+        });
+    if let (Some(module), false) = (error_module, dialect == Dialect::Declarations) {
+        let mut helpers_src = Source::new(indent.clone(), dialect, Members::Functions);
+        unreachable::generate_helpers(module, &mut helpers_src);
+        code += "\n";
+        code.extend(helpers_src.finish().drain(..));
+    }
+
+    if dialect != Dialect::Declarations && enum_options.iter().any(|options| options.equals) {
+        let mut helpers_src = Source::new(indent, dialect, Members::Functions);
+        equality::generate_helpers(&mut helpers_src);
+        code += "\n";
+        code.extend(helpers_src.finish().drain(..));
+    }
+
     code.trim().to_string()
 }
 
-/// Generates `"<O, E>"` or `""` or `"<O, E, R>"` or "<R>" depending on params
-fn braced_generic(generics: &Option<String>, extra_generic_opt: Option<char>) -> String {
-    generics.as_ref().map_or_else(
-        || {
-            extra_generic_opt.map_or_else(String::new, |extra| {
-                let mut s = String::from("<");
-                s.push(extra);
-                s.push_str(">");
-                s
-            })
-        },
-        |gen| {
-            let mut s = String::from("<");
-            s.push_str(&gen);
-            if let Some(extra_generic) = extra_generic_opt {
-                s.push_str(", ");
-                s.push(extra_generic);
+/// Words which can't name a function or type alias, in strict mode JavaScript or TypeScript
+const RESERVED_WORDS: &[&str] = &[
+    "any",
+    "arguments",
+    "await",
+    "bigint",
+    "boolean",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "never",
+    "new",
+    "null",
+    "number",
+    "object",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "string",
+    "super",
+    "switch",
+    "symbol",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "unknown",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        && !RESERVED_WORDS.contains(&name)
+}
+
+/// The variant name as an object key, `Ok` or `"not-found"`
+fn variant_key(t_name: &str) -> Cow<'_, str> {
+    if is_identifier(t_name) {
+        Cow::Borrowed(t_name)
+    } else {
+        Cow::Owned(string_literal(t_name))
+    }
+}
+
+/// Accessing the variant on an object, `.Ok` or `["not-found"]`
+fn variant_member(t_name: &str) -> String {
+    if is_identifier(t_name) {
+        format!(".{}", t_name)
+    } else {
+        format!("[{}]", string_literal(t_name))
+    }
+}
+
+/// `"not-found"`
+fn string_literal(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The enum's type parameters which the variant's contents refer to, so `Ok: O` becomes
+/// `type Ok<O> = O;`. Parameters used by the constraint or default of a used one are kept too.
+fn variant_generics(generics: &[TypeParam], contents: &str) -> Vec<TypeParam> {
+    let mut used: Vec<bool> = generics
+        .iter()
+        .map(|param| refers_to(contents, &param.name))
+        .collect();
+    // later parameters can only depend on earlier ones, so one pass from the back is enough
+    for idx in (0..generics.len()).rev() {
+        if !used[idx] {
+            continue;
+        }
+        let TypeParam {
+            constraint,
+            default,
+            ..
+        } = &generics[idx];
+        for (dep_idx, dep) in generics[..idx].iter().enumerate() {
+            let refers =
+                |opt: &Option<String>| opt.as_ref().is_some_and(|s| refers_to(s, &dep.name));
+            if refers(constraint) || refers(default) {
+                used[dep_idx] = true;
             }
-            s.push_str(">");
-            s
-        },
-    )
+        }
+    }
+    generics
+        .iter()
+        .zip(used)
+        .filter(|(_, used)| *used)
+        .map(|(param, _)| param.clone())
+        .collect()
+}
+
+/// Whether `name` appears as a whole identifier in the type expression, other than as a
+/// property key, tuple label or parameter name like the `O` of `{ O: string }`
+fn refers_to(type_expr: &str, name: &str) -> bool {
+    let is_ident = |chr: char| chr.is_alphanumeric() || chr == '_' || chr == '$';
+    type_expr.match_indices(name).any(|(idx, _)| {
+        let before = &type_expr[..idx];
+        let after = &type_expr[idx + name.len()..];
+        if before.ends_with(is_ident) || after.starts_with(is_ident) {
+            return false;
+        }
+        // `O: string` or `O?: string` after `{`, `(`, `[`, `,`, `;` or `readonly`, but not the
+        // `O` of a conditional type `T extends U ? O : E`
+        let is_key = after
+            .trim_start()
+            .trim_start_matches('?')
+            .trim_start()
+            .starts_with(':')
+            && before
+                .trim_end()
+                .trim_end_matches("readonly")
+                .trim_end()
+                .ends_with(['{', '(', '[', ',', ';']);
+        !is_key
+    })
+}
+
+/// Generates `"<O, E>"` or `""` or `"<O, E, R>"` or "<R>" depending on params
+fn braced_generic(generics: &[TypeParam], extra_generic_opt: Option<char>) -> String {
+    braced_type_params(generics, extra_generic_opt, |param, s| {
+        s.push_str(&param.name)
+    })
+}
+
+/// Generates `"<O extends object, E>"` for declaring functions, which infer their type arguments
+fn braced_generic_decl(generics: &[TypeParam], extra_generic_opt: Option<char>) -> String {
+    braced_type_params(generics, extra_generic_opt, |param, s| {
+        s.push_str(&param.name);
+        if let Some(constraint) = &param.constraint {
+            s.push_str(" extends ");
+            s.push_str(constraint);
+        }
+    })
+}
+
+/// Generates `"<O extends object, E = Error>"` for declaring types
+fn braced_generic_type_decl(generics: &[TypeParam]) -> String {
+    braced_type_params(generics, None, |param, s| {
+        s.push_str(&param.name);
+        if let Some(constraint) = &param.constraint {
+            s.push_str(" extends ");
+            s.push_str(constraint);
+        }
+        if let Some(default) = &param.default {
+            s.push_str(" = ");
+            s.push_str(default);
+        }
+    })
+}
+
+fn braced_type_params(
+    generics: &[TypeParam],
+    extra_generic_opt: Option<char>,
+    push_param: impl Fn(&TypeParam, &mut String),
+) -> String {
+    if generics.is_empty() && extra_generic_opt.is_none() {
+        return String::new();
+    }
+    let mut s = String::from("<");
+    for (idx, param) in generics.iter().enumerate() {
+        if idx > 0 {
+            s.push_str(", ");
+        }
+        push_param(param, &mut s);
+    }
+    if let Some(extra_generic) = extra_generic_opt {
+        if !generics.is_empty() {
+            s.push_str(", ");
+        }
+        s.push(extra_generic);
+    }
+    s.push('>');
+    s
 }
 
 #[cfg(test)]
 mod tests {
-    use super::braced_generic;
+    use super::{
+        braced_generic, braced_generic_decl, braced_generic_type_decl, check_options,
+        variant_generics, Mangle, Options,
+    };
+    use crate::{parse, TypeParam};
 
     #[test]
     fn test_braced_generic() {
-        assert_eq!(braced_generic(&None, None), s(""));
-        assert_eq!(braced_generic(&Some(s("A")), None), s("<A>"));
-        assert_eq!(braced_generic(&Some(s("A")), Some('R')), s("<A, R>"));
-        assert_eq!(braced_generic(&None, Some('R')), s("<R>"));
+        assert_eq!(braced_generic(&[], None), s(""));
+        assert_eq!(braced_generic(&[p("A")], None), s("<A>"));
+        assert_eq!(braced_generic(&[p("A")], Some('R')), s("<A, R>"));
+        assert_eq!(braced_generic(&[], Some('R')), s("<R>"));
         assert_eq!(
-            braced_generic(&Some(s("A, B, C")), Some('R')),
+            braced_generic(&[p("A"), p("B"), p("C")], Some('R')),
             s("<A, B, C, R>")
         );
     }
 
+    #[test]
+    fn test_braced_generic_decl() {
+        let generics = [
+            TypeParam {
+                name: s("T"),
+                constraint: Some(s("object")),
+                default: None,
+            },
+            TypeParam {
+                name: s("E"),
+                constraint: None,
+                default: Some(s("Error")),
+            },
+        ];
+        assert_eq!(braced_generic(&generics, None), s("<T, E>"));
+        assert_eq!(
+            braced_generic_decl(&generics, Some('R')),
+            s("<T extends object, E, R>")
+        );
+        assert_eq!(
+            braced_generic_type_decl(&generics),
+            s("<T extends object, E = Error>")
+        );
+    }
+
+    #[test]
+    fn test_variant_generics() {
+        let generics = [
+            p("T"),
+            TypeParam {
+                name: s("K"),
+                constraint: Some(s("keyof T")),
+                default: None,
+            },
+            p("E"),
+        ];
+        let names = |contents: &str| {
+            variant_generics(&generics, contents)
+                .into_iter()
+                .map(|param| param.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("E"), vec!["E"]);
+        assert_eq!(names("{ key: K; error: Error }"), vec!["T", "K"]);
+        assert_eq!(names("null"), Vec::<String>::new());
+        assert_eq!(
+            names("{ E: string; readonly T?: number }"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            names("[E: number, (T: string) => void]"),
+            Vec::<String>::new()
+        );
+        assert_eq!(names("{ E: E }"), vec!["E"]);
+        assert_eq!(names("T extends string ? E : never"), vec!["T", "E"]);
+        assert_eq!(names("Record<TE, KE>"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_variant_ident() {
+        let pascal = Options::default();
+        let underscore = Options {
+            mangle: Mangle::Underscore,
+            ..Options::default()
+        };
+        assert_eq!(pascal.variant_ident("Ok"), "Ok");
+        assert_eq!(pascal.variant_ident("not-found"), "NotFound");
+        assert_eq!(pascal.variant_ident("404"), "_404");
+        assert_eq!(underscore.variant_ident("not-found"), "not_found");
+        assert_eq!(underscore.variant_ident("404 error"), "_404_error");
+        assert_eq!(pascal.variant_ident("delete"), "Delete");
+        assert_eq!(underscore.variant_ident("delete"), "_delete");
+        assert_eq!(underscore.variant_ident("new-item"), "new_item");
+    }
+
+    #[test]
+    fn test_check_options_directives() {
+        let mut parsed = parse(
+            r#"
+// enum-ts: members=clases, equals, unreachable=handler:
+type Msg = Enum<{
+    Quit: null;
+}>;
+"#,
+        );
+        check_options(&mut parsed, &Options::default());
+        assert!(parsed.errors.is_empty());
+        assert_eq!(
+            parsed.warnings,
+            vec![
+                r#"enum Msg: Unknown enum-ts option: "members=clases""#,
+                r#"enum Msg: Invalid enum-ts unreachable handler: """#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_options() {
+        let source = r#"
+type Lookup = Enum<{
+    "not-found": null;
+    NotFound: string;
+    not_found: number;
+}>;
+"#;
+        let mut parsed = parse(source);
+        check_options(&mut parsed, &Options::default());
+        assert_eq!(
+            parsed.errors,
+            vec!["enum Lookup: variants `not-found` and `NotFound` are both named `NotFound`"]
+        );
+        let mut parsed = parse(&source.replace("\ntype", "\n// enum-ts: mangle=underscore\ntype"));
+        check_options(&mut parsed, &Options::default());
+        assert_eq!(
+            parsed.errors,
+            vec!["enum Lookup: variants `not-found` and `not_found` are both named `not_found`"]
+        );
+        let mut parsed = parse(
+            r#"
+type A = Enum<{
+    A: null;
+}>;
+// enum-ts: unreachable=throw:./errors
+type B = Enum<{
+    B: null;
+}>;
+"#,
+        );
+        let mut options = Options::default();
+        options.apply_directive("unreachable=throw").unwrap();
+        check_options(&mut parsed, &options);
+        assert!(parsed.warnings.is_empty());
+        assert_eq!(
+            parsed.errors,
+            vec!["enum B: `UnexpectedVariantError` is imported from `./errors`, but from `enum-ts-lib` for enum A"]
+        );
+    }
+
+    fn p(name: &str) -> TypeParam {
+        TypeParam {
+            name: s(name),
+            constraint: None,
+            default: None,
+        }
+    }
+
     fn s(string: &str) -> String {
         string.into()
     }
 }
 
+/// Which kind of file the generated code is going into
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Dialect {
+    TypeScript,
+    /// No type annotations
+    JavaScript,
+    /// Only type annotations (`.d.ts`)
+    Declarations,
+}
+
 #[derive(Clone)]
 struct Source {
     indent: String,
     code: String,
+    dialect: Dialect,
+    members: Members,
 }
 
 impl Source {
-    fn new(indent: String) -> Self {
+    fn new(indent: String, dialect: Dialect, members: Members) -> Self {
         Source {
             indent,
             code: String::new(),
+            dialect,
+            members,
         }
     }
     fn new_with_same_settings(&self) -> Self {
         Source {
             code: String::new(),
             indent: self.indent.clone(),
+            dialect: self.dialect,
+            members: self.members,
         }
     }
+    fn emits_types(&self) -> bool {
+        self.dialect != Dialect::JavaScript
+    }
+    fn members(&self) -> Members {
+        self.members
+    }
     fn push(&mut self, s: &str) {
         self.code.push_str(s);
     }
+    /// Push type annotations, which are left out of JavaScript
+    fn push_ts(&mut self, s: &str) {
+        if self.emits_types() {
+            self.push(s);
+        }
+    }
     fn ln_push(&mut self, s: &str) {
-        self.code.push_str("\n");
+        self.code.push('\n');
         self.code.push_str(s);
     }
     fn ln_push_1(&mut self, s: &str) {
-        self.code.push_str("\n");
+        self.code.push('\n');
         self.code.push_str(&self.indent);
         self.code.push_str(s);
     }
+    /// "export function apply", "apply" or "export function applyResult" depending on [`Members`]
+    fn ln_push_member_fn(&mut self, TSEnum { name, export, .. }: &TSEnum, member: &str) {
+        match self.members {
+            Members::Namespace => {
+                self.ln_push("export function ");
+                self.push(member);
+            }
+            Members::Object => self.ln_push(member),
+            Members::Functions => {
+                self.ln_push(if *export {
+                    "export function "
+                } else {
+                    "function "
+                });
+                self.push(member);
+                self.push(name);
+            }
+        }
+    }
+    /// Copies a `/** ... */` comment onto its own lines, so it shows in editor hovers
+    fn ln_push_doc(&mut self, doc: Option<&str>) {
+        for line in doc.into_iter().flat_map(str::lines) {
+            self.ln_push(line.trim_end());
+        }
+    }
+    fn ln_push_member_fn_end(&mut self) {
+        match self.members {
+            Members::Namespace | Members::Functions => self.ln_push("}"),
+            Members::Object => self.ln_push("},"),
+        }
+    }
+    /// Reference to a sibling member from inside of a member function's body
+    fn member_ref(&self, enum_name: &str, member: &str) -> String {
+        match self.members {
+            Members::Namespace => member.to_string(),
+            members => members.qualify(enum_name, member),
+        }
+    }
+    /// Pushes `" {"` and returns true if the function body should follow,
+    /// or ends the signature with `";"` for declarations.
+    fn open_body(&mut self) -> bool {
+        if self.dialect == Dialect::Declarations {
+            self.push(";");
+            false
+        } else {
+            self.push(" {");
+            true
+        }
+    }
     fn push_source(&mut self, other: Self) {
         self.code.extend(other.finish().drain(..));
     }
     fn push_source_1(&mut self, other: Self) {
         let indent_1 = "\n".to_owned() + &self.indent;
         self.code
-            .extend(other.finish().replace("\n", &indent_1).drain(..));
+            .extend(other.finish().replace('\n', &indent_1).drain(..));
     }
     fn push_source_2(&mut self, other: Self) {
         let indent_2 = "\n".to_owned() + &self.indent + &self.indent;
         self.code
-            .extend(other.finish().replace("\n", &indent_2).drain(..));
+            .extend(other.finish().replace('\n', &indent_2).drain(..));
     }
     fn finish(self) -> String {
         self.code
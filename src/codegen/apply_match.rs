@@ -1,95 +1,187 @@
 use super::*;
 
-pub(super) fn generate(
-    TSEnum {
+pub(super) fn generate(ts_enum: &TSEnum, options: &Options, src: &mut Source) {
+    let TSEnum {
         generics,
         name,
         variants,
         ..
-    }: &TSEnum,
-    src: &mut Source,
-) {
+    } = ts_enum;
     // ex "" or "<Ok, Err>"
-    let braced_gen = braced_generic(&generics, None);
+    let braced_gen = braced_generic(generics, None);
     // ex "<R>" or "<Ok, Err, R>"
-    let braced_gen_r = braced_generic(&generics, Some('R'));
+    let braced_gen_r = braced_generic_decl(generics, Some('R'));
+    // "const unexpected = "Unexpected Enum variant for Result<Ok, Err>";"
+    let mut unexpected_src = src.new_with_same_settings();
+    unexpected_src.ln_push("const unexpected = \"Unexpected Enum variant for ");
+    unexpected_src.push(name);
+    unexpected_src.push(&braced_gen);
+    unexpected_src.push("\";");
+    // only the assertion needs a message
+    let uses_message = options.unreachable == Unreachable::Assert;
     let mut apply_src = src.new_with_same_settings();
+    if uses_message
+        && apply_src.members() == Members::Namespace
+        && apply_src.dialect != Dialect::Declarations
+    {
+        apply_src.push_source(unexpected_src.clone());
+    }
+    if apply_src.members() == Members::Functions {
+        // there is no namespace to carry the enum's doc comment
+        apply_src.ln_push_doc(ts_enum.doc.as_deref());
+    }
     // "export function apply<Ok, Err, R>(fns: {"
-    apply_src.ln_push("const unexpected = \"Unexpected Enum variant for ");
-    apply_src.push(&name);
-    apply_src.push(&braced_gen);
-    apply_src.push("\";");
-    apply_src.ln_push("export function apply");
-    apply_src.push(&braced_gen_r);
-    apply_src.push("(fns: {");
+    apply_src.ln_push_member_fn(ts_enum, "apply");
+    apply_src.push_ts(&braced_gen_r);
+    apply_src.push("(fns");
+    apply_src.push_ts(": {");
     //
     let mut match_src = src.new_with_same_settings();
+    if match_src.members() == Members::Functions {
+        match_src.ln_push_doc(ts_enum.doc.as_deref());
+    }
     // "export function match<Ok, Err, R>("
-    match_src.ln_push("export function match");
-    match_src.push(&braced_gen_r);
+    match_src.ln_push_member_fn(ts_enum, "match");
+    match_src.push_ts(&braced_gen_r);
     match_src.push("(");
-    // "value: Result<Ok, Err>,"
-    match_src.ln_push_1("value: ");
-    match_src.push(&name);
-    match_src.push(&braced_gen);
-    match_src.push(",");
-    match_src.ln_push_1("fns: {");
+    if match_src.emits_types() {
+        // "value: Result<Ok, Err>,"
+        match_src.ln_push_1("value: ");
+        match_src.push(name);
+        match_src.push(&braced_gen);
+        match_src.push(",");
+        match_src.ln_push_1("fns: {");
+    } else {
+        match_src.push("value, fns");
+    }
     //
-    for (t_name, content) in variants.iter() {
-        // "Ok(content: Ok): R;"
-        let mut variant_fn_src = src.new_with_same_settings();
-        variant_fn_src.ln_push(&t_name);
-        if content != "null" {
-            variant_fn_src.push("(content: ");
-            variant_fn_src.push(&t_name);
-            variant_fn_src.push(&braced_gen);
-            variant_fn_src.push("): R;");
-        } else {
-            variant_fn_src.push("(): R;");
-        }
+    if src.emits_types() {
+        for (t_name, content) in variants.iter() {
+            // "Ok(content: Ok<O>): R;" or "Move(...content: Move): R;"
+            let mut variant_fn_src = src.new_with_same_settings();
+            variant_fn_src.ln_push_doc(ts_enum.variant_doc(t_name));
+            variant_fn_src.ln_push(&variant_key(t_name));
+            let payload = Payload::of(content);
+            if !matches!(payload, Payload::Unit(_)) {
+                variant_fn_src.push("(");
+                variant_fn_src.push(payload.spread());
+                variant_fn_src.push("content: ");
+                variant_fn_src.push(&options.variant_ident(t_name));
+                variant_fn_src.push(&braced_generic(&variant_generics(generics, content), None));
+                variant_fn_src.push("): R;");
+            } else {
+                variant_fn_src.push("(): R;");
+            }
 
-        apply_src.push_source_1(variant_fn_src.clone());
-        match_src.push_source_2(variant_fn_src);
+            apply_src.push_source_1(variant_fn_src.clone());
+            match_src.push_source_2(variant_fn_src);
+        }
     }
-    apply_src.push("\n}): (value: ");
-    apply_src.push(&name);
-    apply_src.push(&braced_gen);
-    apply_src.push(") => R {");
-    // "return function matchStoplightApply(value) {"
-    apply_src.ln_push_1("return function match");
-    apply_src.push(&name);
-    apply_src.push("Apply(item) {");
-    // "return "Ok" in item"
-    let mut variant_check_src = src.new_with_same_settings();
-    variant_check_src.ln_push("return ");
-    for (t_name, content) in variants.iter() {
-        // ""Ok" in item"
-        variant_check_src.push("\"");
-        variant_check_src.push(&t_name);
-        variant_check_src.push("\" in item");
-        // "? fns.Ok(item.Ok)"
-        variant_check_src.ln_push_1("? fns.");
-        variant_check_src.push(&t_name);
-        if content != "null" {
-            variant_check_src.push("(item.");
-            variant_check_src.push(&t_name);
-            variant_check_src.push(")");
-        } else {
-            variant_check_src.push("()");
+    apply_src.push_ts("\n}");
+    apply_src.push(")");
+    apply_src.push_ts(": (value: ");
+    apply_src.push_ts(name);
+    apply_src.push_ts(&braced_gen);
+    apply_src.push_ts(") => R");
+    if apply_src.open_body() {
+        if uses_message && apply_src.members() != Members::Namespace {
+            apply_src.push_source_1(unexpected_src);
+        }
+        // "return function matchStoplightApply(value) {"
+        apply_src.ln_push_1("return function match");
+        apply_src.push(name);
+        apply_src.push("Apply(item) {");
+        match options.dispatch {
+            Dispatch::Chain => {
+                // "return "Ok" in item"
+                let mut variant_check_src = src.new_with_same_settings();
+                variant_check_src.ln_push("return ");
+                for (t_name, content) in variants.iter() {
+                    // ""Ok" in item"
+                    variant_check_src.push(&string_literal(t_name));
+                    variant_check_src.push(" in item");
+                    // "? fns.Ok(item.Ok)" or "? fns.Move(...item.Move)"
+                    variant_check_src.ln_push_1("? fns");
+                    variant_check_src.push(&variant_member(t_name));
+                    let payload = Payload::of(content);
+                    if !matches!(payload, Payload::Unit(_)) {
+                        variant_check_src.push("(");
+                        variant_check_src.push(payload.spread());
+                        variant_check_src.push("item");
+                        variant_check_src.push(&variant_member(t_name));
+                        variant_check_src.push(")");
+                    } else {
+                        variant_check_src.push("()");
+                    }
+                    variant_check_src.ln_push_1(": ");
+                }
+                // ": (console.assert(false, unexpected, item) as never);"
+                unreachable::generate_fallback(name, &options.unreachable, &mut variant_check_src);
+                variant_check_src.push(";");
+                apply_src.push_source_2(variant_check_src.clone());
+            }
+            Dispatch::Switch => {
+                // "for (const key in item) {"
+                let mut switch_src = src.new_with_same_settings();
+                switch_src.ln_push("for (const key in item) {");
+                switch_src.ln_push_1("switch (key) {");
+                let mut cases_src = src.new_with_same_settings();
+                for (t_name, content) in variants.iter() {
+                    // "case "Ok":"
+                    cases_src.ln_push("case ");
+                    cases_src.push(&string_literal(t_name));
+                    cases_src.push(":");
+                    // "return fns.Ok((item as { Ok: Ok<O> }).Ok);"
+                    cases_src.ln_push_1("return fns");
+                    cases_src.push(&variant_member(t_name));
+                    let payload = Payload::of(content);
+                    if !matches!(payload, Payload::Unit(_)) {
+                        // a switch on the key does not narrow the item
+                        cases_src.push("(");
+                        cases_src.push(payload.spread());
+                        cases_src.push_ts("(item as { ");
+                        cases_src.push_ts(&variant_key(t_name));
+                        cases_src.push_ts(": ");
+                        cases_src.push_ts(&options.variant_ident(t_name));
+                        cases_src
+                            .push_ts(&braced_generic(&variant_generics(generics, content), None));
+                        cases_src.push_ts(" })");
+                        if !cases_src.emits_types() {
+                            cases_src.push("item");
+                        }
+                        cases_src.push(&variant_member(t_name));
+                        cases_src.push(");");
+                    } else {
+                        cases_src.push("();");
+                    }
+                }
+                switch_src.push_source_2(cases_src);
+                switch_src.ln_push_1("}");
+                switch_src.ln_push("}");
+                // "return (console.assert(false, unexpected, item) as never);"
+                switch_src.ln_push("return ");
+                unreachable::generate_fallback(name, &options.unreachable, &mut switch_src);
+                switch_src.push(";");
+                apply_src.push_source_2(switch_src);
+            }
         }
-        variant_check_src.ln_push_1(": ");
+        // apply_src.ln_push_2("return fns[name](contents);");
+        apply_src.ln_push_1("};");
+        apply_src.ln_push_member_fn_end();
     }
-    // ": (console.assert(false, unexpected, item) as never);"
-    variant_check_src.push("(console.assert(false, unexpected, item) as never);");
-    apply_src.push_source_2(variant_check_src.clone());
-    // apply_src.ln_push_2("return fns[name](contents);");
-    apply_src.ln_push_1("};");
-    apply_src.ln_push("}");
 
-    match_src.ln_push_1("}");
-    match_src.ln_push("): R {");
-    match_src.ln_push_1("return apply(fns)(value);");
-    match_src.ln_push("}");
+    if match_src.emits_types() {
+        match_src.ln_push_1("}");
+        match_src.ln_push("): R");
+    } else {
+        match_src.push(")");
+    }
+    if match_src.open_body() {
+        match_src.ln_push_1("return ");
+        match_src.push(&src.member_ref(name, "apply"));
+        match_src.push("(fns)(value);");
+        match_src.ln_push_member_fn_end();
+    }
 
     src.push_source(apply_src);
     src.push_source(match_src);
@@ -1,48 +1,142 @@
 use super::*;
 
-pub(super) fn generate(
-    TSEnum {
+pub(super) fn generate(ts_enum: &TSEnum, options: &Options, src: &mut Source) {
+    let TSEnum {
         export,
-        generics,
+        generics: enum_generics,
+        name,
         variants,
         ..
-    }: &TSEnum,
-    src: &mut Source,
-) {
-    // ex "" or "<Ok, Err>"
-    let braced_gen = braced_generic(&generics, None);
+    } = ts_enum;
     for (t_name, contents) in variants.iter() {
-        // "export function Ok<O, E>("
+        let ident = options.variant_ident(t_name);
+        // only the generics of the variant, so they can be inferred from the contents
+        let generics = variant_generics(enum_generics, contents);
+        // ex "" or "<O>"
+        let braced_gen = braced_generic(&generics, None);
+        // ex "" or "<O extends object>"
+        let braced_gen_decl = braced_generic_decl(&generics, None);
+        // "export function Ok<O>("
+        src.ln_push_doc(ts_enum.variant_doc(t_name));
         src.ln_push("");
         if *export {
             src.push("export ");
         }
         src.push("function ");
-        src.push(&t_name);
-        src.push(&braced_gen);
+        src.push(&ident);
+        src.push_ts(&braced_gen_decl);
         src.push("(");
-        if contents != "null" {
-            // "contents: Ok<O, E>"
-            src.push("contents: ");
+        let payload = Payload::of(contents);
+        if !matches!(payload, Payload::Unit(_)) {
+            // "contents: Ok<O>" or "...contents: Move"
+            src.push(payload.spread());
+            src.push("contents");
+            src.push_ts(": ");
             // note: should be defined by type_aliases
-            src.push(&t_name);
-            src.push(&braced_gen);
+            src.push_ts(&ident);
+            src.push_ts(&braced_gen);
         }
-        // "): { Ok: Ok<O, E> } {"
-        src.push("): { ");
-        src.push(&t_name);
-        src.push(": ");
-        src.push(&t_name);
-        src.push(&braced_gen);
-        src.push(" } {");
-        // "return { Ok: contents };"
-        src.ln_push_1("return { ");
-        src.push(&t_name);
-        if contents != "null" {
-            src.push(": contents };");
+        src.push(")");
+        match options.creators {
+            Creators::Variant => {
+                // "): { Ok: Ok<O> } {"
+                src.push_ts(": { ");
+                src.push_ts(options.readonly_key());
+                src.push_ts(&variant_key(t_name));
+                src.push_ts(": ");
+                src.push_ts(&ident);
+                src.push_ts(&braced_gen);
+                src.push_ts(" }");
+            }
+            Creators::Enum => {
+                // "): Result<O, never> {" or "): Readonly<Result<O, never>> {"
+                src.push_ts(": ");
+                if options.readonly {
+                    src.push_ts("Readonly<");
+                }
+                src.push_ts(name);
+                if !enum_generics.is_empty() {
+                    let args: Vec<&str> = enum_generics
+                        .iter()
+                        .map(|param| {
+                            if generics.iter().any(|used| used.name == param.name) {
+                                param.name.as_str()
+                            } else {
+                                "never"
+                            }
+                        })
+                        .collect();
+                    src.push_ts("<");
+                    src.push_ts(&args.join(", "));
+                    src.push_ts(">");
+                }
+                if options.readonly {
+                    src.push_ts(">");
+                }
+            }
+        }
+        if src.open_body() {
+            // "return { Ok: contents };" or "return { Red: 0 };"
+            let value = match payload {
+                Payload::Tuple | Payload::Value => "contents",
+                Payload::Unit(value) => value,
+            };
+            src.ln_push_1("return ");
+            src.push(&options.variant_value(t_name, value));
+            src.push(";");
+            src.ln_push("}");
+        }
+    }
+}
+
+/// Constants for the variants without contents, like `Stoplight.Red`
+pub(super) fn generate_constants(ts_enum: &TSEnum, options: &Options, src: &mut Source) {
+    let TSEnum {
+        export,
+        name,
+        variants,
+        ..
+    } = ts_enum;
+    for (t_name, contents) in variants.iter() {
+        let value = match Payload::of(contents) {
+            Payload::Unit(value) => value,
+            Payload::Tuple | Payload::Value => continue,
+        };
+        src.ln_push_doc(ts_enum.variant_doc(t_name));
+        let ident = options.variant_ident(t_name);
+        // "{ Red: Red }"
+        let ty = format!(
+            "{{ {}{}: {} }}",
+            options.readonly_key(),
+            variant_key(t_name),
+            ident
+        );
+        // "{ Red: 0 }"
+        let constant = options.variant_value(t_name, value);
+        if src.members() == Members::Object {
+            // "Red: { Red: 0 } as { Red: Red },"
+            src.ln_push(&ident);
+            src.push(": ");
+            src.push(&constant);
+            src.push_ts(" as ");
+            src.push_ts(&ty);
+            src.push(",");
+            continue;
+        }
+        // "export const Red: { Red: Red } = { Red: 0 };"
+        if src.members() == Members::Namespace {
+            src.ln_push("export const ");
+            src.push(&ident);
         } else {
-            src.push(": null };");
+            src.ln_push(if *export { "export const " } else { "const " });
+            src.push(&src.members().qualify(name, &ident));
+        }
+        src.push_ts(": ");
+        src.push_ts(&ty);
+        if src.dialect != Dialect::Declarations {
+            src.push(" = ");
+            src.push(&constant);
         }
-        src.ln_push("}");
+        src.push(";");
     }
 }
@@ -0,0 +1,254 @@
+use super::*;
+
+pub(super) fn generate(
+    ts_enum: &TSEnum,
+    options: &Options,
+    comparable: &[(&TSEnum, Members)],
+    src: &mut Source,
+) {
+    let TSEnum {
+        generics,
+        name,
+        variants,
+        ..
+    } = ts_enum;
+    // ex "" or "<Ok, Err>"
+    let braced_gen = braced_generic(generics, None);
+    // ex "" or "<Ok extends object, Err>"
+    let braced_gen_decl = braced_generic_decl(generics, None);
+    let mut equals_src = src.new_with_same_settings();
+    // "export function equals<Ok, Err>("
+    equals_src.ln_push_member_fn(ts_enum, "equals");
+    equals_src.push_ts(&braced_gen_decl);
+    equals_src.push("(");
+    if equals_src.emits_types() {
+        // "a: Result<Ok, Err>,"
+        for param in ["a", "b"].iter() {
+            equals_src.ln_push_1(param);
+            equals_src.push(": ");
+            equals_src.push(name);
+            equals_src.push(&braced_gen);
+            equals_src.push(",");
+        }
+        if equals_src.dialect == Dialect::Declarations {
+            // initializers are not allowed in declarations, so optional instead
+            equals_src.ln_push_1("eqFns?: {");
+        } else {
+            equals_src.ln_push_1("eqFns: {");
+        }
+        let mut eq_fns_src = src.new_with_same_settings();
+        for (t_name, content) in variants.iter() {
+            // "Ok?(a: Ok<O>, b: Ok<O>): boolean;"
+            let variant_gen = braced_generic(&variant_generics(generics, content), None);
+            let ident = options.variant_ident(t_name);
+            eq_fns_src.ln_push(&variant_key(t_name));
+            eq_fns_src.push("?(a: ");
+            eq_fns_src.push(&ident);
+            eq_fns_src.push(&variant_gen);
+            eq_fns_src.push(", b: ");
+            eq_fns_src.push(&ident);
+            eq_fns_src.push(&variant_gen);
+            eq_fns_src.push("): boolean;");
+        }
+        equals_src.push_source_2(eq_fns_src);
+        if equals_src.dialect == Dialect::Declarations {
+            equals_src.ln_push_1("}");
+        } else {
+            equals_src.ln_push_1("} = {}");
+        }
+        equals_src.ln_push("): boolean");
+    } else {
+        equals_src.push("a, b, eqFns = {})");
+    }
+    if equals_src.open_body() {
+        equals_src.ln_push_1("if (a === b) return true;");
+        equals_src.ln_push_1("if (a == null || b == null) return false;");
+        // "return "Ok" in a"
+        let mut variant_check_src = src.new_with_same_settings();
+        variant_check_src.ln_push("return ");
+        for (t_name, content) in variants.iter() {
+            // ""Ok" in a"
+            let member = variant_member(t_name);
+            variant_check_src.push(&string_literal(t_name));
+            variant_check_src.push(" in a");
+            // "? "Ok" in b && (eqFns.Ok ? eqFns.Ok(a.Ok, b.Ok) : enumTsDeepEqual(a.Ok, b.Ok))"
+            variant_check_src.ln_push_1("? ");
+            variant_check_src.push(&string_literal(t_name));
+            variant_check_src.push(" in b && (eqFns");
+            variant_check_src.push(&member);
+            variant_check_src.push(" ? eqFns");
+            variant_check_src.push(&member);
+            variant_check_src.push(&payload_args(t_name));
+            variant_check_src.push(" : ");
+            match nested_enum(content, comparable) {
+                Some((nested, members)) => {
+                    variant_check_src.push(&members.qualify(nested, "equals"));
+                }
+                None => variant_check_src.push("enumTsDeepEqual"),
+            }
+            variant_check_src.push(&payload_args(t_name));
+            variant_check_src.push(")");
+            variant_check_src.ln_push_1(": ");
+        }
+        variant_check_src.push("false;");
+        equals_src.push_source_1(variant_check_src);
+        equals_src.ln_push_member_fn_end();
+    }
+
+    let mut hash_src = src.new_with_same_settings();
+    // "export function hash<Ok, Err>(value: Result<Ok, Err>): number {"
+    hash_src.ln_push_member_fn(ts_enum, "hash");
+    hash_src.push_ts(&braced_gen_decl);
+    hash_src.push("(value");
+    hash_src.push_ts(": ");
+    hash_src.push_ts(name);
+    hash_src.push_ts(&braced_gen);
+    hash_src.push(")");
+    hash_src.push_ts(": number");
+    if hash_src.open_body() {
+        let mut variant_hash_src = src.new_with_same_settings();
+        variant_hash_src.ln_push("return ");
+        for (t_name, content) in variants.iter() {
+            // ""Ok" in value"
+            variant_hash_src.push(&string_literal(t_name));
+            variant_hash_src.push(" in value");
+            // "? enumTsHashCombine(enumTsHashString("Ok"), enumTsDeepHash(value.Ok))"
+            variant_hash_src.ln_push_1("? enumTsHashCombine(enumTsHashString(");
+            variant_hash_src.push(&string_literal(t_name));
+            variant_hash_src.push("), ");
+            match nested_enum(content, comparable) {
+                Some((nested, members)) => {
+                    variant_hash_src.push(&members.qualify(nested, "hash"));
+                }
+                None => variant_hash_src.push("enumTsDeepHash"),
+            }
+            variant_hash_src.push("(value");
+            variant_hash_src.push(&variant_member(t_name));
+            variant_hash_src.push("))");
+            variant_hash_src.ln_push_1(": ");
+        }
+        variant_hash_src.push("0;");
+        hash_src.push_source_1(variant_hash_src);
+        hash_src.ln_push_member_fn_end();
+    }
+
+    src.push_source(equals_src);
+    src.push_source(hash_src);
+}
+
+/// Shared helpers referenced by every generated `equals` and `hash`, emitted once per file
+pub(super) fn generate_helpers(src: &mut Source) {
+    let types = src.emits_types();
+    // ex ": unknown" or ""
+    let ty = |annotation: &'static str| if types { annotation } else { "" };
+    // ex "(a as any)" or "a"
+    let any = |name: &str| {
+        if types {
+            format!("({} as any)", name)
+        } else {
+            name.to_string()
+        }
+    };
+    src.ln_push(&format!(
+        "function enumTsDeepEqual(a{}, b{}){} {{",
+        ty(": unknown"),
+        ty(": unknown"),
+        ty(": boolean"),
+    ));
+    src.ln_push_1("if (a === b || (a !== a && b !== b)) return true;");
+    src.ln_push_1("if (typeof a !== \"object\" || a === null) return false;");
+    src.ln_push_1("if (typeof b !== \"object\" || b === null) return false;");
+    src.ln_push_1("if (Array.isArray(a) !== Array.isArray(b)) return false;");
+    src.ln_push_1("const keys = Object.keys(a);");
+    src.ln_push_1("if (keys.length !== Object.keys(b).length) return false;");
+    src.ln_push_1("for (const key of keys) {");
+    let mut loop_src = src.new_with_same_settings();
+    loop_src.ln_push("if (");
+    loop_src.ln_push_1("!Object.prototype.hasOwnProperty.call(b, key) ||");
+    loop_src.ln_push_1(&format!(
+        "!enumTsDeepEqual({}[key], {}[key])",
+        any("a"),
+        any("b")
+    ));
+    loop_src.ln_push(")");
+    loop_src.ln_push_1("return false;");
+    src.push_source_2(loop_src);
+    src.ln_push_1("}");
+    src.ln_push_1("return true;");
+    src.ln_push("}");
+    src.ln_push(&format!(
+        "function enumTsDeepHash(value{}){} {{",
+        ty(": unknown"),
+        ty(": number"),
+    ));
+    src.ln_push_1("if (typeof value !== \"object\" || value === null) {");
+    let mut primitive_src = src.new_with_same_settings();
+    primitive_src.ln_push("return enumTsHashString(typeof value + String(value));");
+    src.push_source_2(primitive_src);
+    src.ln_push_1("}");
+    src.ln_push_1("let hash = Array.isArray(value) ? 2 : 3;");
+    // sorted so that equal objects with different key order hash the same
+    src.ln_push_1("for (const key of Object.keys(value).sort()) {");
+    let mut loop_src = src.new_with_same_settings();
+    loop_src.ln_push("hash = enumTsHashCombine(hash, enumTsHashString(key));");
+    loop_src.ln_push(&format!(
+        "hash = enumTsHashCombine(hash, enumTsDeepHash({}[key]));",
+        any("value")
+    ));
+    src.push_source_2(loop_src);
+    src.ln_push_1("}");
+    src.ln_push_1("return hash;");
+    src.ln_push("}");
+    src.ln_push(&format!(
+        "function enumTsHashString(str{}){} {{",
+        ty(": string"),
+        ty(": number"),
+    ));
+    src.ln_push_1("let hash = 0;");
+    src.ln_push_1(
+        "for (let i = 0; i < str.length; i++) hash = enumTsHashCombine(hash, str.charCodeAt(i));",
+    );
+    src.ln_push_1("return hash;");
+    src.ln_push("}");
+    src.ln_push(&format!(
+        "function enumTsHashCombine(hash{}, next{}){} {{",
+        ty(": number"),
+        ty(": number"),
+        ty(": number"),
+    ));
+    src.ln_push_1("return (Math.imul(hash, 31) + next) | 0;");
+    src.ln_push("}");
+}
+
+/// "(a.Ok, b.Ok)"
+fn payload_args(t_name: &str) -> String {
+    let member = variant_member(t_name);
+    format!("(a{}, b{})", member, member)
+}
+
+/// Name of the enum if the variant contents are exactly another enum with `equals` (e.g. `BinaryTree<T>`)
+fn nested_enum<'a>(
+    content: &str,
+    comparable: &[(&'a TSEnum, Members)],
+) -> Option<(&'a str, Members)> {
+    let content = content.trim();
+    let (type_name, type_args) = content.split_at(content.find('<').unwrap_or(content.len()));
+    if !type_args.is_empty() {
+        // the generic arguments must close at the very end, so `A<T> | B<T>` is not a match
+        let mut depth = 0;
+        for (idx, chr) in type_args.char_indices() {
+            match chr {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 && idx != type_args.len() - 1 {
+                return None;
+            }
+        }
+    }
+    comparable
+        .iter()
+        .find(|(ts_enum, _)| ts_enum.name == type_name)
+        .map(|(ts_enum, members)| (ts_enum.name.as_str(), *members))
+}
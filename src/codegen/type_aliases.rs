@@ -1,28 +1,53 @@
 use super::*;
 
-pub(super) fn generate(
-    TSEnum {
+pub(super) fn generate(ts_enum: &TSEnum, options: &Options, src: &mut Source) {
+    let TSEnum {
         export,
         generics,
+        name,
         variants,
+        doc,
         ..
-    }: &TSEnum,
-    src: &mut Source,
-) {
-    // ex "" or "<Ok, Err>"
-    let braced_gen = braced_generic(&generics, None);
+    } = ts_enum;
+    if !src.emits_types() {
+        return;
+    }
+    // ex "" or "<Ok extends object, Err = Error>"
+    let braced_gen_decl = braced_generic_type_decl(generics);
+    if src.dialect == Dialect::Declarations {
+        // the `Enum<{ ... }>` declaration stays behind in the source file
+        // "export type Result<O, E> = { Ok: Ok<O> } | { Err: Err<E> };"
+        src.ln_push_doc(doc.as_deref());
+        src.ln_push("export type ");
+        src.push(name);
+        src.push(&braced_gen_decl);
+        src.push(" =");
+        for (idx, (t_name, contents)) in variants.iter().enumerate() {
+            src.push(if idx == 0 { " { " } else { " | { " });
+            src.push(options.readonly_key());
+            src.push(&variant_key(t_name));
+            src.push(": ");
+            src.push(&options.variant_ident(t_name));
+            src.push(&braced_generic(&variant_generics(generics, contents), None));
+            src.push(" }");
+        }
+        src.push(";");
+    }
     for (t_name, contents) in variants.iter() {
-        // "export type Ok<O, E>"
+        // "export type Ok<O>"
+        src.ln_push_doc(ts_enum.variant_doc(t_name));
         src.ln_push("");
         if *export {
             src.push("export ");
         }
         src.push("type ");
-        src.push(&t_name);
-        src.push(&braced_gen);
+        src.push(&options.variant_ident(t_name));
+        src.push(&braced_generic_type_decl(&variant_generics(
+            generics, contents,
+        )));
         // " = O;"
         src.push(" = ");
-        src.push(&contents);
+        src.push(contents);
         src.push(";");
     }
 }
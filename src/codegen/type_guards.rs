@@ -1,41 +1,47 @@
 use super::*;
 
-pub(super) fn generate(
-    TSEnum {
+pub(super) fn generate(ts_enum: &TSEnum, options: &Options, src: &mut Source) {
+    let TSEnum {
         export,
         generics,
         name,
         variants,
         ..
-    }: &TSEnum,
-    src: &mut Source,
-) {
-    let braced_gen = braced_generic(&generics, None);
-    for (t_name, _) in variants.iter() {
+    } = ts_enum;
+    let braced_gen = braced_generic(generics, None);
+    // ex "" or "<Ok extends object, Err>"
+    let braced_gen_decl = braced_generic_decl(generics, None);
+    for (t_name, contents) in variants.iter() {
         // "export function isOk<O, E>("
+        src.ln_push_doc(ts_enum.variant_doc(t_name));
         src.ln_push("");
         if *export {
             src.push("export ");
         }
         src.push("function is");
-        src.push(&t_name);
-        src.push(&braced_gen);
+        src.push(&options.variant_ident(t_name));
+        src.push_ts(&braced_gen_decl);
         src.push("(");
         // "item: Result<O, E>"
-        src.push("item: ");
-        src.push(&name);
-        src.push(&braced_gen);
-        // "): item is { Ok: O } {"
-        src.push("): item is { ");
-        src.push(&t_name);
-        src.push(": ");
-        src.push(&t_name);
-        src.push(&braced_gen);
-        src.push(" } {");
-        // "return item != null && "Ok" in item;"
-        src.ln_push_1("return item != null && \"");
-        src.push(&t_name);
-        src.push("\" in item;");
-        src.ln_push("}");
+        src.push("item");
+        src.push_ts(": ");
+        src.push_ts(name);
+        src.push_ts(&braced_gen);
+        // "): item is { Ok: Ok<O> } {"
+        src.push(")");
+        src.push_ts(": item is { ");
+        src.push_ts(options.readonly_key());
+        src.push_ts(&variant_key(t_name));
+        src.push_ts(": ");
+        src.push_ts(&options.variant_ident(t_name));
+        src.push_ts(&braced_generic(&variant_generics(generics, contents), None));
+        src.push_ts(" }");
+        if src.open_body() {
+            // "return item != null && "Ok" in item;"
+            src.ln_push_1("return item != null && ");
+            src.push(&string_literal(t_name));
+            src.push(" in item;");
+            src.ln_push("}");
+        }
     }
 }
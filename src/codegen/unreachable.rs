@@ -0,0 +1,45 @@
+use super::*;
+
+/// The last branch of `apply`'s conditional chain, after every variant was checked for
+pub(super) fn generate_fallback(name: &str, unreachable: &Unreachable, src: &mut Source) {
+    match unreachable {
+        Unreachable::Assert => {
+            // "(console.assert(false, unexpected, item) as never)"
+            src.push_ts("(");
+            src.push("console.assert(false, unexpected, item)");
+            src.push_ts(" as never)");
+        }
+        Unreachable::Throw(_) => {
+            // "enumTsThrowUnexpected("Result", item)"
+            src.push("enumTsThrowUnexpected(\"");
+            src.push(name);
+            src.push("\", item)");
+        }
+        Unreachable::Handler(handler) => {
+            // "(onUnexpected("Result", item) as never)"
+            src.push_ts("(");
+            src.push(handler);
+            src.push("(\"");
+            src.push(name);
+            src.push("\", item)");
+            src.push_ts(" as never)");
+        }
+    }
+}
+
+/// The import of `UnexpectedVariantError` and its thrower, emitted once per file
+pub(super) fn generate_helpers(module: &str, src: &mut Source) {
+    // aliased so it can't clash with the file importing it itself
+    src.ln_push("import { UnexpectedVariantError as EnumTsUnexpectedVariantError } from ");
+    src.push(&string_literal(module));
+    src.push(";");
+    src.ln_push("function enumTsThrowUnexpected(enumName");
+    src.push_ts(": string");
+    src.push(", value");
+    src.push_ts(": unknown");
+    src.push(")");
+    src.push_ts(": never");
+    src.push(" {");
+    src.ln_push_1("throw new EnumTsUnexpectedVariantError(enumName, value);");
+    src.ln_push("}");
+}
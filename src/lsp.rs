@@ -0,0 +1,262 @@
+//! A minimal language server on stdio, offering the generated region update as document
+//! formatting and as a `source.fixAll.enum-ts` code action.
+use crate::*;
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+const FIX_ALL_KIND: &str = "source.fixAll.enum-ts";
+
+pub fn run(options: &Options) -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut server = Server::new(options.clone());
+    while let Some(message) = read_message(&mut input)? {
+        for response in server.handle(message) {
+            let body = response.to_string();
+            let mut output = stdout.lock();
+            write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+            output.flush()?;
+        }
+        if let Some(code) = server.exit_code {
+            std::process::exit(code);
+        }
+    }
+    Ok(())
+}
+
+/// Reads a `Content-Length` framed JSON-RPC message, or `None` at the end of input
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = length.trim().parse::<usize>().ok();
+        }
+    }
+    let length = content_length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+struct Server {
+    options: Options,
+    /// Contents of the open documents by uri
+    documents: HashMap<String, String>,
+    shutdown: bool,
+    exit_code: Option<i32>,
+}
+
+impl Server {
+    fn new(options: Options) -> Self {
+        Server {
+            options,
+            documents: HashMap::new(),
+            shutdown: false,
+            exit_code: None,
+        }
+    }
+
+    /// Responds to a request or notification
+    fn handle(&mut self, message: Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    // full document sync
+                    "textDocumentSync": 1,
+                    "documentFormattingProvider": true,
+                    "codeActionProvider": { "codeActionKinds": [FIX_ALL_KIND] },
+                },
+                "serverInfo": { "name": "enum-ts", "version": VERSION },
+            }),
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                if let (Some(uri), Some(text)) =
+                    (document["uri"].as_str(), document["text"].as_str())
+                {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                return Vec::new();
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str();
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                return Vec::new();
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                    self.documents.remove(uri);
+                }
+                return Vec::new();
+            }
+            "textDocument/formatting" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                Value::Array(self.edit(uri).into_iter().collect())
+            }
+            "textDocument/codeAction" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let wanted = match params["context"]["only"].as_array() {
+                    Some(only) => only
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .any(|kind| FIX_ALL_KIND.starts_with(kind)),
+                    None => true,
+                };
+                match self.edit(uri).filter(|_| wanted) {
+                    Some(edit) => json!([{
+                        "title": "Update enum-ts generated code",
+                        "kind": FIX_ALL_KIND,
+                        "edit": { "changes": { uri: [edit] } },
+                    }]),
+                    None => json!([]),
+                }
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                Value::Null
+            }
+            "exit" => {
+                self.exit_code = Some(if self.shutdown { 0 } else { 1 });
+                return Vec::new();
+            }
+            _ if message.get("id").is_some() => {
+                return vec![json!({
+                    "jsonrpc": "2.0",
+                    "id": message["id"],
+                    "error": { "code": -32601, "message": format!("Unhandled method {}", method) },
+                })];
+            }
+            // other notifications, like "initialized"
+            _ => return Vec::new(),
+        };
+        vec![json!({ "jsonrpc": "2.0", "id": message["id"], "result": result })]
+    }
+
+    /// The `TextEdit` updating the generated region of an open document, if it is out of date
+    fn edit(&self, uri: &str) -> Option<Value> {
+        let text = self.documents.get(uri)?;
+        let mut parsed = match uri_path(uri) {
+            Some(path) => parse_file(&path, text),
+            None => parse(text),
+        };
+        check_options(&mut parsed, &self.options);
+        // updating without the enums which could not be parsed would remove their code
+        if !parsed.errors.is_empty() {
+            return None;
+        }
+        let (start, end, new_text) = make_edit_offsets(text, parsed, false, &self.options)?;
+        Some(json!({
+            "range": { "start": position(text, start), "end": position(text, end) },
+            "newText": new_text,
+        }))
+    }
+}
+
+/// The LSP position of a byte offset, with the character counted in UTF-16 code units
+fn position(text: &str, offset: usize) -> Value {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+/// The path of a `file://` uri, so that imported variants can be resolved
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = match tail {
+            [hi, lo, ..] if byte == b'%' => std::str::from_utf8(&[*hi, *lo])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_open_document() {
+        let mut server = Server::new(Options::default());
+        let initialized = server
+            .handle(json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }));
+        assert_eq!(
+            initialized[0]["result"]["capabilities"]["documentFormattingProvider"],
+            true
+        );
+        let uri = "file:///does/not%20exist/stoplight.ts";
+        let text = "// ü\ntype Stoplight = Enum<{\n    Green: 0;\n    Red: 0;\n}>;\n\n";
+        server.handle(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "languageId": "typescript", "version": 1, "text": text } },
+        }));
+        let formatted = server.handle(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/formatting",
+            "params": { "textDocument": { "uri": uri }, "options": {} },
+        }));
+        let edit = &formatted[0]["result"][0];
+        assert_eq!(edit["range"]["start"], json!({ "line": 6, "character": 0 }));
+        assert!(edit["newText"]
+            .as_str()
+            .unwrap()
+            .contains("function isGreen"));
+        let unknown = server.handle(
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/hover", "params": {} }),
+        );
+        assert_eq!(unknown[0]["error"]["code"], -32601);
+        server.handle(json!({ "jsonrpc": "2.0", "id": 4, "method": "shutdown" }));
+        server.handle(json!({ "jsonrpc": "2.0", "method": "exit" }));
+        assert_eq!(server.exit_code, Some(0));
+    }
+
+    #[test]
+    fn decode_uri_path() {
+        assert_eq!(
+            uri_path("file:///a%20b/c.ts"),
+            Some(PathBuf::from("/a b/c.ts"))
+        );
+        assert_eq!(uri_path("untitled:Untitled-1"), None);
+    }
+}
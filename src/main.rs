@@ -1,24 +1,34 @@
 use ignore::WalkBuilder;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use std::any::Any;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
 use std::{env, io};
 
+mod cache;
 mod codegen;
+mod lsp;
 mod parser;
+mod report;
 mod string_utils;
 mod write;
 
 pub use codegen::*;
 pub use parser::*;
+pub use report::*;
 pub use write::*;
 
 pub(crate) use string_utils::*;
 
 pub(crate) mod prelude {
-    pub use crate::{Parsed, TSEnum};
+    pub use crate::{Parsed, Payload, TSEnum, TypeParam};
 }
 
-const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 fn main() {
     let mode = match args::Mode::from_args(
@@ -26,28 +36,50 @@ fn main() {
         env::args().collect(),
     ) {
         Ok(mode) => mode,
-        Err(err) => exit_err(err),
+        // prints help and version too, which exit successfully
+        Err(err) => err.exit(),
     };
 
     match mode {
-        args::Mode::Pipe(mode) => pipe_mode(mode),
+        args::Mode::Pipe(mode, options, path) => pipe_mode(mode, options, path),
         args::Mode::Write(write_options) => write_mode(write_options),
+        args::Mode::Lsp(options) => {
+            if let Err(err) = lsp::run(&options) {
+                exit_err(err);
+            }
+        }
+        args::Mode::Completions(shell) => args::print_completions(shell),
     }
 }
 
-fn pipe_mode(mode: args::PipeMode) {
+fn pipe_mode(mode: args::PipeMode, options: Options, path: Option<PathBuf>) {
     let mut input = String::new();
     let stdin = io::stdin();
     loop {
         match stdin.read_line(&mut input) {
             Ok(0) => {
+                let mut parsed = match &path {
+                    Some(path) => parse_file(path, &input),
+                    None => parse(&input),
+                };
+                check_options(&mut parsed, &options);
+                if let args::PipeMode::ReplaceRangeVSCode | args::PipeMode::FullFile = mode {
+                    check_module(&input, &mut parsed, &options);
+                }
+                if !parsed.errors.is_empty() {
+                    exit_err(parsed.errors.join("\n"));
+                }
+                for warning in parsed.warnings.iter() {
+                    eprintln!("Warning: {}", warning);
+                }
                 match mode {
-                    args::PipeMode::ShowGenerated => {
-                        let parsed = parse(&input);
-                        println!("{}", generate(parsed));
+                    args::PipeMode::Generated => {
+                        println!("{}", generate_with(parsed, &options));
                     }
-                    args::PipeMode::ShowReplaceRangeVSCode => {
-                        if let Some((start, end, to_write)) = make_edit(&input, true) {
+                    args::PipeMode::ReplaceRangeVSCode => {
+                        if let Some((start, end, to_write)) =
+                            make_edit_parsed(&input, parsed, true, &options)
+                        {
                             eprintln!(
                                 "update-range: L{}:{}-L{}:{}",
                                 start.line, start.col, end.line, end.col
@@ -57,8 +89,14 @@ fn pipe_mode(mode: args::PipeMode) {
                             eprintln!("no-update");
                         }
                     }
-                    args::PipeMode::ShowFullFile => {
-                        if let Some(to_write) = rewrite(&input, true) {
+                    args::PipeMode::JavaScript => {
+                        println!("{}", generate_js(parsed, &options));
+                    }
+                    args::PipeMode::Declarations => {
+                        println!("{}", generate_declarations(parsed, &options));
+                    }
+                    args::PipeMode::FullFile => {
+                        if let Some(to_write) = rewrite_parsed(&input, parsed, true, &options) {
                             eprintln!("Updated");
                             println!("{}", to_write);
                         } else {
@@ -72,7 +110,7 @@ fn pipe_mode(mode: args::PipeMode) {
             Ok(_number_of_bytes_read) => {
                 // collected into input
             }
-            Err(error) => eprintln!("error: {}", error),
+            Err(error) => exit_err(error),
         }
     }
 }
@@ -100,8 +138,22 @@ fn write_mode(mut options: args::WriteOptions) {
         }
     }
 
-    let write = !options.dry_run;
+    if let Some(jobs) = options.jobs {
+        walk_builder.threads(jobs.get());
+    }
+
+    let action = options.action;
+    let clean = options.clean;
     let force = options.force_updates;
+    let codegen = &options.codegen;
+    let js = options.js;
+    let format = options.format;
+    let cache = options.cache.map(|location| {
+        let context = cache::context(codegen, js);
+        Mutex::new(cache::Cache::load(location, context))
+    });
+    let started = Instant::now();
+    let results = Mutex::new(Vec::new());
     walk_builder.build_parallel().run(|| {
         Box::new(
             |entry: std::result::Result<ignore::DirEntry, ignore::Error>| match &entry {
@@ -110,118 +162,742 @@ fn write_mode(mut options: args::WriteOptions) {
                         if file_type.is_file()
                             && RE_TYPESCRIPT_FILE.is_match(&dir.file_name().to_string_lossy())
                         {
-                            rewrite_file(dir.path(), write, force);
+                            let write = action.writes();
+                            // a bug hit by one file is reported for it, instead of the thread
+                            // exiting and leaving the others waiting for it
+                            let update =
+                                panic::catch_unwind(AssertUnwindSafe(|| match (clean, js) {
+                                    (false, _) => update_file(
+                                        dir.path(),
+                                        write,
+                                        force,
+                                        js,
+                                        codegen,
+                                        cache.as_ref(),
+                                    ),
+                                    (true, false) => clean_file(dir.path(), write),
+                                    (true, true) => clean_js_files(dir.path(), write),
+                                }));
+                            results.lock().unwrap().push(FileResult {
+                                path: dir.path().to_path_buf(),
+                                update: match update {
+                                    Ok(update) => update.map_err(|err| err.to_string()),
+                                    Err(panic) => Err(panic_message(panic)),
+                                },
+                            });
                         }
                     }
                     ignore::WalkState::Continue
                 }
                 Err(err) => {
-                    eprintln!("Error encountered (skipping): {}", err);
+                    results.lock().unwrap().push(walk_error(err));
                     ignore::WalkState::Skip
                 }
             },
         )
-    })
+    });
+
+    // only a write saves the cache, so checks leave the project untouched, and forced updates
+    // don't look files up in it, so saving it would drop every entry
+    if let Some(cache) = cache.filter(|_| action == Action::Write && !force) {
+        if let Err(err) = cache.into_inner().unwrap().save() {
+            eprintln!("Warning: could not save the cache: {}", err);
+        }
+    }
+
+    // printed in path order rather than the order the threads finished in
+    let mut results = results.into_inner().unwrap();
+    results.sort_by(|a, b| a.path.cmp(&b.path));
+    let report = Report {
+        action,
+        clean,
+        results,
+        elapsed: started.elapsed(),
+    };
+    match format {
+        args::Format::Text => {
+            for result in report.results.iter() {
+                if let Ok(update) = &result.update {
+                    for change in update.changes.iter() {
+                        action.print(change);
+                    }
+                }
+            }
+            for (path, warning) in report.warnings() {
+                eprintln!("Warning: {}: {}", path.display(), warning);
+            }
+            for (path, err) in report.errors() {
+                eprintln!("Error: {}: {}", path.display(), err);
+            }
+            eprintln!("{}", report.summary());
+            if report.exit_code() == 1 {
+                eprintln!("Run `enum-ts write` to update");
+            }
+        }
+        args::Format::Json => println!("{}", report.to_json()),
+    }
+    std::process::exit(report.exit_code());
+}
+
+/// Updates the generated code for the file at `path`, unless the cache shows it is unchanged
+/// since it was up to date
+fn update_file(
+    path: &Path,
+    write: bool,
+    force: bool,
+    js: bool,
+    codegen: &Options,
+    cache: Option<&Mutex<cache::Cache>>,
+) -> io::Result<FileUpdate> {
+    let outputs = if js {
+        let (js_path, dts_path) = js_file_paths(path);
+        vec![js_path, dts_path]
+    } else {
+        Vec::new()
+    };
+    let cached = |enums| FileUpdate {
+        enums,
+        cached: true,
+        ..FileUpdate::default()
+    };
+    let cache = cache.filter(|_| !force);
+    let stamp = cache::Stamp::of(&fs::metadata(path)?);
+    if let Some(enums) =
+        cache.and_then(|cache| cache.lock().unwrap().unchanged(path, stamp, &outputs))
+    {
+        return Ok(cached(enums));
+    }
+    let contents = fs::read_to_string(path)?;
+    // files with nothing to update are found without hashing them, and are recorded with an
+    // empty hash, which matches while they still have nothing to update
+    let hash = match cache {
+        Some(_) if needs_parse(&contents) => cache::hash_contents(contents.as_bytes()),
+        _ => String::new(),
+    };
+    if let Some(enums) = cache.and_then(|cache| {
+        cache
+            .lock()
+            .unwrap()
+            .same_contents(path, stamp, &hash, &outputs)
+    }) {
+        return Ok(cached(enums));
+    }
+    let update = if js {
+        write_js_source(path, &contents, write, force, codegen)
+    } else {
+        rewrite_source(path, contents, write, force, codegen)
+    };
+    if let Some(cache) = cache {
+        match &update {
+            // a file which imports its variants could go out of date without changing itself,
+            // and the warnings for a file are printed until they are fixed
+            Ok(update)
+                if !update.imports
+                    && update.warnings.is_empty()
+                    && (write || update.changes.is_empty()) =>
+            {
+                let hash = if update.changes.iter().any(|change| change.path == path) {
+                    fs::read(path)
+                        .ok()
+                        .map(|written| cache::hash_contents(&written))
+                } else {
+                    Some(hash)
+                };
+                if let Some(hash) = hash {
+                    let mut cache = cache.lock().unwrap();
+                    cache.record(path, &hash, update.enums, &outputs);
+                }
+            }
+            _ => cache.lock().unwrap().forget(path),
+        }
+    }
+    update
+}
+
+/// The message a thread panicked with, like "internal error: ..."
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    let message = match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => String::from("unknown panic"),
+        },
+    };
+    format!("internal error: {}", message)
+}
+
+/// The path an error walking the tree happened at, if it has one, and the reason
+fn walk_error(err: &ignore::Error) -> FileResult {
+    match err {
+        ignore::Error::WithPath { path, err } => FileResult {
+            path: path.clone(),
+            update: Err(err.to_string()),
+        },
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            walk_error(err)
+        }
+        err => FileResult {
+            path: PathBuf::new(),
+            update: Err(err.to_string()),
+        },
+    }
 }
 
 fn exit_err<E: std::fmt::Display>(err: E) -> ! {
     eprintln!("Failed to execute enum-ts\n{}", err);
-    std::process::exit(1);
+    std::process::exit(2);
 }
 
 mod args {
+    use crate::{Action, Options};
+    use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+    use clap_complete::Shell;
+    use std::num::NonZeroUsize;
     use std::path::PathBuf;
 
     #[derive(Debug)]
     pub struct WriteOptions {
-        pub dry_run: bool,
+        pub action: Action,
+        /// Remove the generated code instead of updating it
+        pub clean: bool,
         pub force_updates: bool,
+        /// Write `.enum.js` and `.enum.d.ts` files next to sources instead of regions
+        pub js: bool,
         pub base_dir: PathBuf,
-        pub paths: Vec<String>,
-        pub ignore_files: Vec<String>,
+        pub paths: Vec<PathBuf>,
+        pub ignore_files: Vec<PathBuf>,
+        pub codegen: Options,
+        pub format: Format,
+        /// Number of threads, or `None` to pick from the number of CPUs
+        pub jobs: Option<NonZeroUsize>,
+        /// Where to keep the record of up to date files, or `None` to not use one
+        pub cache: Option<PathBuf>,
         // eventually support globs and stuff https://docs.rs/ignore/0.4.17/ignore/overrides/index.html
         // pub ignore: Vec<String>,
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq)]
     pub enum PipeMode {
-        ShowGenerated,
-        ShowReplaceRangeVSCode,
-        ShowFullFile,
+        Generated,
+        ReplaceRangeVSCode,
+        FullFile,
+        JavaScript,
+        Declarations,
     }
 
+    #[derive(Debug)]
     pub enum Mode {
-        Pipe(PipeMode),
+        /// With the path of the file on stdin, if given, to resolve imports from
+        Pipe(PipeMode, Options, Option<PathBuf>),
         Write(WriteOptions),
+        Lsp(Options),
+        Completions(Shell),
     }
 
-    impl Mode {
-        pub fn from_args(cwd: PathBuf, args: Vec<String>) -> Result<Self, String> {
-            let mut args_iterator = args.into_iter().peekable();
-            let _executable = args_iterator.next();
-            match args_iterator.peek() {
-                Some(arg) => match arg.as_str() {
-                    "--edit-l1c0" => {
-                        // one arg for edit mode
-                        return Ok(Mode::Pipe(PipeMode::ShowReplaceRangeVSCode));
-                    }
-                    "--full" => {
-                        // one arg for edit mode
-                        return Ok(Mode::Pipe(PipeMode::ShowFullFile));
-                    }
-                    _ => {}
-                },
-                None => {
-                    // no args will always be pipe mode
-                    return Ok(Mode::Pipe(PipeMode::ShowGenerated));
-                }
+    /// TypeScript Enum pattern matcher codegen
+    #[derive(Parser, Debug)]
+    #[command(name = "enum-ts", version = crate::VERSION, disable_version_flag = true)]
+    struct Cli {
+        /// Print version
+        #[arg(short = 'v', long, action = clap::ArgAction::Version)]
+        version: Option<bool>,
+        #[command(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Subcommand, Debug)]
+    enum Command {
+        /// Print the code generated for the enums in a file read from stdin
+        Generate {
+            /// What to generate
+            #[arg(long, value_enum, default_value_t = Target::Ts)]
+            target: Target,
+            #[command(flatten)]
+            stdin: StdinArgs,
+            #[command(flatten)]
+            codegen: CodegenArgs,
+        },
+        /// Update the generated code in every *.ts and *.tsx file
+        Write {
+            /// Print the updated files instead of writing them
+            #[arg(short = 'n', long)]
+            dry_run: bool,
+            #[command(flatten)]
+            update: UpdateArgs,
+        },
+        /// List files with out of date generated code, exiting with 1 if there are any
+        Check {
+            #[command(flatten)]
+            update: UpdateArgs,
+        },
+        /// Print a diff of the updates `write` would make, exiting with 1 if there are any
+        Diff {
+            #[command(flatten)]
+            update: UpdateArgs,
+        },
+        /// Remove every generated region, or with `--js` the generated files
+        Clean {
+            /// Print what would be removed instead of removing it
+            #[arg(short = 'n', long)]
+            dry_run: bool,
+            #[command(flatten)]
+            walk: WalkArgs,
+        },
+        /// Update the generated code in a file read from stdin, for editor integrations
+        Pipe {
+            /// Print the updated file (default)
+            #[arg(long, conflicts_with = "edit_l1c0")]
+            full: bool,
+            /// Print only the updated region, with its range on stderr as `update-range: L1:0-L2:0`
+            #[arg(long = "edit-l1c0")]
+            edit_l1c0: bool,
+            #[command(flatten)]
+            stdin: StdinArgs,
+            #[command(flatten)]
+            codegen: CodegenArgs,
+        },
+        /// Run a language server on stdio which updates generated code on format
+        Lsp {
+            #[command(flatten)]
+            codegen: CodegenArgs,
+        },
+        /// Print a shell completion script
+        Completions {
+            #[arg(value_enum)]
+            shell: Shell,
+        },
+    }
+
+    #[derive(ValueEnum, Clone, Copy, Debug)]
+    enum Target {
+        /// TypeScript, as in generated regions
+        Ts,
+        /// JavaScript, as in `.enum.js` files
+        Js,
+        /// Typings, as in `.enum.d.ts` files
+        Dts,
+    }
+
+    #[derive(Args, Debug)]
+    struct WalkArgs {
+        /// Files or directories to search, defaulting to the current directory
+        paths: Vec<PathBuf>,
+        /// Use `.enum.js` and `.enum.d.ts` files next to each source instead of regions
+        #[arg(long)]
+        js: bool,
+        /// Additional gitignore-style file of paths to skip
+        #[arg(long = "ignore-file", value_name = "FILE")]
+        ignore_files: Vec<PathBuf>,
+        /// How to print the results
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+        /// Number of files to process in parallel, defaulting to the number of CPUs
+        #[arg(short, long, value_name = "N")]
+        jobs: Option<NonZeroUsize>,
+    }
+
+    #[derive(Args, Debug)]
+    struct StdinArgs {
+        /// Path of the file given on stdin, to find the variants it imports from relative modules
+        #[arg(long, value_name = "FILE")]
+        stdin_path: Option<PathBuf>,
+    }
+
+    #[derive(Args, Debug)]
+    struct UpdateArgs {
+        #[command(flatten)]
+        walk: WalkArgs,
+        /// Regenerate even if the generated code's hash is up to date
+        #[arg(short, long)]
+        force: bool,
+        /// Process every file instead of skipping those unchanged since they were up to date
+        #[arg(long)]
+        no_cache: bool,
+        /// Where to keep the cache, by default `node_modules/.cache/enum-ts/cache.json` or
+        /// `.enum-ts-cache`
+        #[arg(long, value_name = "FILE", conflicts_with = "no_cache")]
+        cache_file: Option<PathBuf>,
+        #[command(flatten)]
+        codegen: CodegenArgs,
+    }
+
+    #[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+    pub enum Format {
+        /// Each out of date file as it is found, then a summary on stderr
+        Text,
+        /// A JSON object of the results for each file and the summary counts
+        Json,
+    }
+
+    /// Code generation options, mirroring the `// enum-ts: equals` directives
+    #[derive(Args, Debug)]
+    #[command(next_help_heading = "Code generation")]
+    struct CodegenArgs {
+        /// Generate `equals` and `hash` helpers
+        #[arg(long)]
+        equals: bool,
+        /// Generate constants like `Stoplight.Red` for variants without contents
+        #[arg(long)]
+        constants: bool,
+        /// Mark variant keys `readonly`
+        #[arg(long)]
+        readonly: bool,
+        /// `Object.freeze` the values made by creators and constants
+        #[arg(long)]
+        freeze: bool,
+        /// How `apply`/`match` are grouped under the enum's name
+        #[arg(long, value_name = "STYLE", value_parser = ["namespace", "object", "functions"])]
+        members: Option<String>,
+        /// What `apply` does with a value which is not one of the variants
+        #[arg(long, value_name = "assert|throw|throw:MODULE|handler:NAME", value_parser = parse_unreachable)]
+        unreachable: Option<String>,
+        /// How `apply` finds the variant of a value
+        #[arg(long, value_name = "STYLE", value_parser = ["chain", "switch"])]
+        dispatch: Option<String>,
+        /// What the creator functions return
+        #[arg(long, value_name = "TYPE", value_parser = ["variant", "enum"])]
+        creators: Option<String>,
+        /// How quoted variant names become identifiers
+        #[arg(long, value_name = "STYLE", value_parser = ["pascal", "underscore"])]
+        mangle: Option<String>,
+    }
+
+    impl CodegenArgs {
+        fn options(&self) -> Options {
+            let flags = [
+                ("equals", self.equals),
+                ("constants", self.constants),
+                ("readonly", self.readonly),
+                ("freeze", self.freeze),
+            ];
+            let values = [
+                ("members", &self.members),
+                ("unreachable", &self.unreachable),
+                ("dispatch", &self.dispatch),
+                ("creators", &self.creators),
+                ("mangle", &self.mangle),
+            ];
+            let directives = flags
+                .iter()
+                .filter(|(_, on)| *on)
+                .map(|(key, _)| key.to_string())
+                .chain(values.iter().filter_map(|(key, value)| {
+                    value.as_ref().map(|value| format!("{}={}", key, value))
+                }));
+            let mut options = Options::default();
+            for directive in directives {
+                options
+                    .apply_directive(&directive)
+                    .expect("option values are checked while parsing arguments");
             }
+            options
+        }
+    }
 
-            let mut write_options = WriteOptions {
-                dry_run: true,
-                force_updates: false,
-                base_dir: cwd,
-                paths: Vec::new(),
-                // ignore: Vec::new(),
-                ignore_files: Vec::new(),
+    fn parse_unreachable(value: &str) -> Result<String, String> {
+        Options::default().apply_directive(&format!("unreachable={}", value))?;
+        Ok(value.to_string())
+    }
+
+    impl Mode {
+        pub fn from_args(cwd: PathBuf, args: Vec<String>) -> Result<Self, clap::Error> {
+            let cli = Cli::try_parse_from(legacy_args(args))?;
+            let walk = |action: Action,
+                        clean: bool,
+                        walk: WalkArgs,
+                        force: bool,
+                        codegen: Options,
+                        cache: Option<Option<PathBuf>>| {
+                let cache = cache.map(|file| match file {
+                    Some(file) => cwd.join(file),
+                    None => crate::cache::default_location(&cwd),
+                });
+                Mode::Write(WriteOptions {
+                    action,
+                    clean,
+                    force_updates: force,
+                    js: walk.js,
+                    base_dir: cwd.clone(),
+                    paths: walk.paths,
+                    ignore_files: walk.ignore_files,
+                    codegen,
+                    format: walk.format,
+                    jobs: walk.jobs,
+                    cache,
+                })
             };
+            Ok(match cli.command {
+                Command::Generate {
+                    target,
+                    stdin,
+                    codegen,
+                } => {
+                    let mode = match target {
+                        Target::Ts => PipeMode::Generated,
+                        Target::Js => PipeMode::JavaScript,
+                        Target::Dts => PipeMode::Declarations,
+                    };
+                    Mode::Pipe(
+                        mode,
+                        codegen.options(),
+                        stdin.stdin_path.map(|path| cwd.join(path)),
+                    )
+                }
+                Command::Write {
+                    dry_run,
+                    update:
+                        UpdateArgs {
+                            walk: args,
+                            force,
+                            no_cache,
+                            cache_file,
+                            codegen,
+                        },
+                } => {
+                    let action = if dry_run {
+                        Action::DryRun
+                    } else {
+                        Action::Write
+                    };
+                    walk(
+                        action,
+                        false,
+                        args,
+                        force,
+                        codegen.options(),
+                        (!no_cache).then_some(cache_file),
+                    )
+                }
+                Command::Check {
+                    update:
+                        UpdateArgs {
+                            walk: args,
+                            force,
+                            no_cache,
+                            cache_file,
+                            codegen,
+                        },
+                } => walk(
+                    Action::Check,
+                    false,
+                    args,
+                    force,
+                    codegen.options(),
+                    (!no_cache).then_some(cache_file),
+                ),
+                Command::Diff {
+                    update:
+                        UpdateArgs {
+                            walk: args,
+                            force,
+                            no_cache,
+                            cache_file,
+                            codegen,
+                        },
+                } => walk(
+                    Action::Diff,
+                    false,
+                    args,
+                    force,
+                    codegen.options(),
+                    (!no_cache).then_some(cache_file),
+                ),
+                Command::Clean {
+                    dry_run,
+                    walk: args,
+                } => {
+                    let action = if dry_run {
+                        Action::DryRun
+                    } else {
+                        Action::Write
+                    };
+                    walk(action, true, args, false, Options::default(), None)
+                }
+                Command::Pipe {
+                    edit_l1c0,
+                    stdin,
+                    codegen,
+                    ..
+                } => {
+                    let mode = if edit_l1c0 {
+                        PipeMode::ReplaceRangeVSCode
+                    } else {
+                        PipeMode::FullFile
+                    };
+                    Mode::Pipe(
+                        mode,
+                        codegen.options(),
+                        stdin.stdin_path.map(|path| cwd.join(path)),
+                    )
+                }
+                Command::Lsp { codegen } => Mode::Lsp(codegen.options()),
+                Command::Completions { shell } => Mode::Completions(shell),
+            })
+        }
+    }
+
+    /// Translates the arguments from before there were subcommands, which editor integrations
+    /// still use: no arguments to generate, `--edit-l1c0`, `--full`, `--emit-js`, `--emit-dts`
+    /// to pipe, and otherwise a dry run unless given `--write`.
+    fn legacy_args(args: Vec<String>) -> Vec<String> {
+        let mut args = args.into_iter();
+        let executable = args.next().unwrap_or_else(|| "enum-ts".to_string());
+        let mut rest: Vec<String> = args.collect();
+        let is_current = rest.first().is_some_and(|first| {
+            let cli = Cli::command();
+            first.starts_with("-h")
+                || ["--help", "-v", "--version"].contains(&first.as_str())
+                || cli.get_subcommands().any(|cmd| cmd.get_name() == first)
+                || first == "help"
+        });
+        if is_current {
+            return std::iter::once(executable).chain(rest).collect();
+        }
+        let take = |rest: &mut Vec<String>, flags: &[&str]| {
+            let found = rest.iter().any(|arg| flags.contains(&arg.as_str()));
+            rest.retain(|arg| !flags.contains(&arg.as_str()));
+            found
+        };
+        let mut translated: Vec<String> = if rest.is_empty() {
+            vec!["generate".into()]
+        } else if take(&mut rest, &["--edit-l1c0"]) {
+            vec!["pipe".into(), "--edit-l1c0".into()]
+        } else if take(&mut rest, &["--full"]) {
+            vec!["pipe".into(), "--full".into()]
+        } else if take(&mut rest, &["--emit-js"]) {
+            vec!["generate".into(), "--target=js".into()]
+        } else if take(&mut rest, &["--emit-dts"]) {
+            vec!["generate".into(), "--target=dts".into()]
+        } else if take(&mut rest, &["-w", "--write"]) {
+            vec!["write".into()]
+        } else {
+            vec!["write".into(), "--dry-run".into()]
+        };
+        translated.insert(0, executable);
+        translated.extend(rest);
+        translated
+    }
 
-            let mut errors = Vec::new();
+    pub fn print_completions(shell: Shell) {
+        clap_complete::generate(
+            shell,
+            &mut Cli::command(),
+            "enum-ts",
+            &mut std::io::stdout(),
+        );
+    }
 
-            while let Some(next_option) = args_iterator.next() {
-                match next_option.as_str() {
-                    "-v" | "--version" => {
-                        // show version
-                        eprintln!("v{}", &crate::VERSION);
-                    }
-                    "-w" | "--write" => {
-                        write_options.dry_run = false;
-                    }
-                    "-f" | "--force" => {
-                        write_options.force_updates = true;
-                    }
-                    "--ignore-file" => {
-                        if let Some(ignore_file) = args_iterator.next() {
-                            write_options.ignore_files.push(ignore_file);
-                        } else {
-                            errors.push(format!("File must be specified following the `{}` argument like `{} .ignore-file`", &next_option, &next_option));
-                        }
-                    }
-                    other => {
-                        if other.starts_with('-') {
-                            errors.push(format!("Unknown option: {:?}", other));
-                        } else {
-                            write_options.paths.push(next_option);
-                        }
-                    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn mode(args: &str) -> Result<Mode, clap::Error> {
+            Mode::from_args(
+                PathBuf::from("/repo"),
+                std::iter::once("enum-ts")
+                    .chain(args.split_whitespace())
+                    .map(String::from)
+                    .collect(),
+            )
+        }
+
+        #[test]
+        fn parse_subcommands() {
+            assert!(matches!(
+                mode("generate --target js --equals"),
+                Ok(Mode::Pipe(
+                    PipeMode::JavaScript,
+                    Options { equals: true, .. },
+                    None
+                ))
+            ));
+            assert!(matches!(
+                mode("pipe --members=functions --edit-l1c0"),
+                Ok(Mode::Pipe(PipeMode::ReplaceRangeVSCode, _, _))
+            ));
+            match mode("check src --force --ignore-file .gitignore --dispatch switch -j 2") {
+                Ok(Mode::Write(options)) => {
+                    assert_eq!(options.action, Action::Check);
+                    assert_eq!(options.jobs, NonZeroUsize::new(2));
+                    assert!(options.force_updates);
+                    assert_eq!(options.paths, vec![PathBuf::from("src")]);
+                    assert_eq!(options.ignore_files, vec![PathBuf::from(".gitignore")]);
+                    assert_eq!(options.codegen.dispatch, crate::Dispatch::Switch);
+                    assert_eq!(options.cache, Some(PathBuf::from("/repo/.enum-ts-cache")));
                 }
+                other => panic!("{:?}", other),
             }
-
-            if !errors.is_empty() {
-                super::exit_err(format!("Errors:\n - {}", errors.join("\n - ")));
+            match mode("diff --no-cache") {
+                Ok(Mode::Write(options)) => assert_eq!(options.cache, None),
+                other => panic!("{:?}", other),
             }
+            match mode("write --cache-file tmp/cache.json") {
+                Ok(Mode::Write(options)) => {
+                    assert_eq!(options.cache, Some(PathBuf::from("/repo/tmp/cache.json")))
+                }
+                other => panic!("{:?}", other),
+            }
+            assert!(mode("write --no-cache --cache-file cache.json").is_err());
+            match mode("--edit-l1c0 --stdin-path src/a.ts") {
+                Ok(Mode::Pipe(PipeMode::ReplaceRangeVSCode, _, path)) => {
+                    assert_eq!(path, Some(PathBuf::from("/repo/src/a.ts")))
+                }
+                other => panic!("{:?}", other),
+            }
+            assert!(mode("pipe --full --edit-l1c0").is_err());
+            assert!(mode("write --unreachable=handler:").is_err());
+            assert!(mode("write --unreachable=throw:").is_err());
+            assert!(mode("write --members=classes").is_err());
+            assert!(mode("write --jobs 0").is_err());
+        }
 
-            Ok(Mode::Write(write_options))
+        #[test]
+        fn parse_legacy_args() {
+            assert!(matches!(
+                mode(""),
+                Ok(Mode::Pipe(PipeMode::Generated, _, _))
+            ));
+            // pipe flags are no longer only recognized first
+            assert!(matches!(
+                mode("--equals --edit-l1c0"),
+                Ok(Mode::Pipe(
+                    PipeMode::ReplaceRangeVSCode,
+                    Options { equals: true, .. },
+                    None
+                ))
+            ));
+            assert!(matches!(
+                mode("--full"),
+                Ok(Mode::Pipe(PipeMode::FullFile, _, _))
+            ));
+            assert!(matches!(
+                mode("--emit-dts"),
+                Ok(Mode::Pipe(PipeMode::Declarations, _, _))
+            ));
+            assert!(matches!(
+                mode("src --write --js"),
+                Ok(Mode::Write(WriteOptions {
+                    action: Action::Write,
+                    js: true,
+                    ..
+                }))
+            ));
+            assert!(matches!(
+                mode("."),
+                Ok(Mode::Write(WriteOptions {
+                    action: Action::DryRun,
+                    ..
+                }))
+            ));
+            // exits with the version instead of continuing into a dry run
+            assert_eq!(
+                mode("-v").unwrap_err().kind(),
+                clap::error::ErrorKind::DisplayVersion
+            );
+            assert_eq!(
+                mode("--help").unwrap_err().kind(),
+                clap::error::ErrorKind::DisplayHelp
+            );
         }
     }
 }
@@ -255,25 +931,25 @@ export type Stoplight = Enum<{
 }>;
             "###,
         )), @r###"
-type Ok<O, E> = O;
-type Err<O, E> = E;
-function Ok<O, E>(contents: Ok<O, E>): { Ok: Ok<O, E> } {
+type Ok<O> = O;
+type Err<E> = E;
+function Ok<O>(contents: Ok<O>): { Ok: Ok<O> } {
     return { Ok: contents };
 }
-function Err<O, E>(contents: Err<O, E>): { Err: Err<O, E> } {
+function Err<E>(contents: Err<E>): { Err: Err<E> } {
     return { Err: contents };
 }
-function isOk<O, E>(item: Result<O, E>): item is { Ok: Ok<O, E> } {
+function isOk<O, E>(item: Result<O, E>): item is { Ok: Ok<O> } {
     return item != null && "Ok" in item;
 }
-function isErr<O, E>(item: Result<O, E>): item is { Err: Err<O, E> } {
+function isErr<O, E>(item: Result<O, E>): item is { Err: Err<E> } {
     return item != null && "Err" in item;
 }
 namespace Result {
     const unexpected = "Unexpected Enum variant for Result<O, E>";
     export function apply<O, E, R>(fns: {
-        Ok(content: Ok<O, E>): R;
-        Err(content: Err<O, E>): R;
+        Ok(content: Ok<O>): R;
+        Err(content: Err<E>): R;
     }): (value: Result<O, E>) => R {
         return function matchResultApply(item) {
             return "Ok" in item
@@ -286,8 +962,8 @@ namespace Result {
     export function match<O, E, R>(
         value: Result<O, E>,
         fns: {
-            Ok(content: Ok<O, E>): R;
-            Err(content: Err<O, E>): R;
+            Ok(content: Ok<O>): R;
+            Err(content: Err<E>): R;
         }
     ): R {
         return apply(fns)(value);
@@ -299,8 +975,8 @@ export type Branch = {
     left: Nested,
     right: Nested,
 };
-export function Leaf(contents: Leaf): { Leaf: Leaf } {
-    return { Leaf: contents };
+export function Leaf(): { Leaf: Leaf } {
+    return { Leaf: 0 };
 }
 export function Branch(contents: Branch): { Branch: Branch } {
     return { Branch: contents };
@@ -314,12 +990,12 @@ export function isBranch(item: Nested): item is { Branch: Branch } {
 export namespace Nested {
     const unexpected = "Unexpected Enum variant for Nested";
     export function apply<R>(fns: {
-        Leaf(content: Leaf): R;
+        Leaf(): R;
         Branch(content: Branch): R;
     }): (value: Nested) => R {
         return function matchNestedApply(item) {
             return "Leaf" in item
-                ? fns.Leaf(item.Leaf)
+                ? fns.Leaf()
                 : "Branch" in item
                 ? fns.Branch(item.Branch)
                 : (console.assert(false, unexpected, item) as never);
@@ -328,7 +1004,7 @@ export namespace Nested {
     export function match<R>(
         value: Nested,
         fns: {
-            Leaf(content: Leaf): R;
+            Leaf(): R;
             Branch(content: Branch): R;
         }
     ): R {
@@ -339,14 +1015,14 @@ export namespace Nested {
 export type Green = 0;
 export type Yellow = 0;
 export type Red = 0;
-export function Green(contents: Green): { Green: Green } {
-    return { Green: contents };
+export function Green(): { Green: Green } {
+    return { Green: 0 };
 }
-export function Yellow(contents: Yellow): { Yellow: Yellow } {
-    return { Yellow: contents };
+export function Yellow(): { Yellow: Yellow } {
+    return { Yellow: 0 };
 }
-export function Red(contents: Red): { Red: Red } {
-    return { Red: contents };
+export function Red(): { Red: Red } {
+    return { Red: 0 };
 }
 export function isGreen(item: Stoplight): item is { Green: Green } {
     return item != null && "Green" in item;
@@ -360,26 +1036,1140 @@ export function isRed(item: Stoplight): item is { Red: Red } {
 export namespace Stoplight {
     const unexpected = "Unexpected Enum variant for Stoplight";
     export function apply<R>(fns: {
-        Green(content: Green): R;
-        Yellow(content: Yellow): R;
-        Red(content: Red): R;
+        Green(): R;
+        Yellow(): R;
+        Red(): R;
     }): (value: Stoplight) => R {
         return function matchStoplightApply(item) {
             return "Green" in item
-                ? fns.Green(item.Green)
+                ? fns.Green()
                 : "Yellow" in item
-                ? fns.Yellow(item.Yellow)
+                ? fns.Yellow()
                 : "Red" in item
-                ? fns.Red(item.Red)
+                ? fns.Red()
                 : (console.assert(false, unexpected, item) as never);
         };
     }
     export function match<R>(
         value: Stoplight,
         fns: {
-            Green(content: Green): R;
-            Yellow(content: Yellow): R;
-            Red(content: Red): R;
+            Green(): R;
+            Yellow(): R;
+            Red(): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+"###)
+    }
+
+    #[test]
+    fn generate_equals_with_directive() {
+        assert_display_snapshot!(generate(parse(
+            r###"
+// enum-ts: equals
+export type BinaryTree<T> = Enum<{
+    Leaf: T;
+    Branch: {
+        left: BinaryTree<T>;
+        right: BinaryTree<T>;
+    };
+}>;
+
+// enum-ts: equals
+export type Forest<T> = Enum<{
+    Tree: BinaryTree<T>;
+    Empty: null;
+}>;
+            "###,
+        )), @r###"
+export type Leaf<T> = T;
+export type Branch<T> = {
+    left: BinaryTree<T>;
+    right: BinaryTree<T>;
+};
+export function Leaf<T>(contents: Leaf<T>): { Leaf: Leaf<T> } {
+    return { Leaf: contents };
+}
+export function Branch<T>(contents: Branch<T>): { Branch: Branch<T> } {
+    return { Branch: contents };
+}
+export function isLeaf<T>(item: BinaryTree<T>): item is { Leaf: Leaf<T> } {
+    return item != null && "Leaf" in item;
+}
+export function isBranch<T>(item: BinaryTree<T>): item is { Branch: Branch<T> } {
+    return item != null && "Branch" in item;
+}
+export namespace BinaryTree {
+    const unexpected = "Unexpected Enum variant for BinaryTree<T>";
+    export function apply<T, R>(fns: {
+        Leaf(content: Leaf<T>): R;
+        Branch(content: Branch<T>): R;
+    }): (value: BinaryTree<T>) => R {
+        return function matchBinaryTreeApply(item) {
+            return "Leaf" in item
+                ? fns.Leaf(item.Leaf)
+                : "Branch" in item
+                ? fns.Branch(item.Branch)
+                : (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<T, R>(
+        value: BinaryTree<T>,
+        fns: {
+            Leaf(content: Leaf<T>): R;
+            Branch(content: Branch<T>): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+    export function equals<T>(
+        a: BinaryTree<T>,
+        b: BinaryTree<T>,
+        eqFns: {
+            Leaf?(a: Leaf<T>, b: Leaf<T>): boolean;
+            Branch?(a: Branch<T>, b: Branch<T>): boolean;
+        } = {}
+    ): boolean {
+        if (a === b) return true;
+        if (a == null || b == null) return false;
+        return "Leaf" in a
+            ? "Leaf" in b && (eqFns.Leaf ? eqFns.Leaf(a.Leaf, b.Leaf) : enumTsDeepEqual(a.Leaf, b.Leaf))
+            : "Branch" in a
+            ? "Branch" in b && (eqFns.Branch ? eqFns.Branch(a.Branch, b.Branch) : enumTsDeepEqual(a.Branch, b.Branch))
+            : false;
+    }
+    export function hash<T>(value: BinaryTree<T>): number {
+        return "Leaf" in value
+            ? enumTsHashCombine(enumTsHashString("Leaf"), enumTsDeepHash(value.Leaf))
+            : "Branch" in value
+            ? enumTsHashCombine(enumTsHashString("Branch"), enumTsDeepHash(value.Branch))
+            : 0;
+    }
+}
+
+export type Tree<T> = BinaryTree<T>;
+export type Empty = null;
+export function Tree<T>(contents: Tree<T>): { Tree: Tree<T> } {
+    return { Tree: contents };
+}
+export function Empty(): { Empty: Empty } {
+    return { Empty: null };
+}
+export function isTree<T>(item: Forest<T>): item is { Tree: Tree<T> } {
+    return item != null && "Tree" in item;
+}
+export function isEmpty<T>(item: Forest<T>): item is { Empty: Empty } {
+    return item != null && "Empty" in item;
+}
+export namespace Forest {
+    const unexpected = "Unexpected Enum variant for Forest<T>";
+    export function apply<T, R>(fns: {
+        Tree(content: Tree<T>): R;
+        Empty(): R;
+    }): (value: Forest<T>) => R {
+        return function matchForestApply(item) {
+            return "Tree" in item
+                ? fns.Tree(item.Tree)
+                : "Empty" in item
+                ? fns.Empty()
+                : (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<T, R>(
+        value: Forest<T>,
+        fns: {
+            Tree(content: Tree<T>): R;
+            Empty(): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+    export function equals<T>(
+        a: Forest<T>,
+        b: Forest<T>,
+        eqFns: {
+            Tree?(a: Tree<T>, b: Tree<T>): boolean;
+            Empty?(a: Empty, b: Empty): boolean;
+        } = {}
+    ): boolean {
+        if (a === b) return true;
+        if (a == null || b == null) return false;
+        return "Tree" in a
+            ? "Tree" in b && (eqFns.Tree ? eqFns.Tree(a.Tree, b.Tree) : BinaryTree.equals(a.Tree, b.Tree))
+            : "Empty" in a
+            ? "Empty" in b && (eqFns.Empty ? eqFns.Empty(a.Empty, b.Empty) : enumTsDeepEqual(a.Empty, b.Empty))
+            : false;
+    }
+    export function hash<T>(value: Forest<T>): number {
+        return "Tree" in value
+            ? enumTsHashCombine(enumTsHashString("Tree"), BinaryTree.hash(value.Tree))
+            : "Empty" in value
+            ? enumTsHashCombine(enumTsHashString("Empty"), enumTsDeepHash(value.Empty))
+            : 0;
+    }
+}
+
+function enumTsDeepEqual(a: unknown, b: unknown): boolean {
+    if (a === b || (a !== a && b !== b)) return true;
+    if (typeof a !== "object" || a === null) return false;
+    if (typeof b !== "object" || b === null) return false;
+    if (Array.isArray(a) !== Array.isArray(b)) return false;
+    const keys = Object.keys(a);
+    if (keys.length !== Object.keys(b).length) return false;
+    for (const key of keys) {
+        if (
+            !Object.prototype.hasOwnProperty.call(b, key) ||
+            !enumTsDeepEqual((a as any)[key], (b as any)[key])
+        )
+            return false;
+    }
+    return true;
+}
+function enumTsDeepHash(value: unknown): number {
+    if (typeof value !== "object" || value === null) {
+        return enumTsHashString(typeof value + String(value));
+    }
+    let hash = Array.isArray(value) ? 2 : 3;
+    for (const key of Object.keys(value).sort()) {
+        hash = enumTsHashCombine(hash, enumTsHashString(key));
+        hash = enumTsHashCombine(hash, enumTsDeepHash((value as any)[key]));
+    }
+    return hash;
+}
+function enumTsHashString(str: string): number {
+    let hash = 0;
+    for (let i = 0; i < str.length; i++) hash = enumTsHashCombine(hash, str.charCodeAt(i));
+    return hash;
+}
+function enumTsHashCombine(hash: number, next: number): number {
+    return (Math.imul(hash, 31) + next) | 0;
+}
+"###)
+    }
+
+    #[test]
+    fn generate_js_and_declarations() {
+        let source = r###"
+type Result<O, E> = Enum<{
+    Ok: O;
+    Err: E;
+}>;
+            "###;
+        assert_display_snapshot!(generate_js(parse(source), &Options::default()), @r###"
+export function Ok(contents) {
+    return { Ok: contents };
+}
+export function Err(contents) {
+    return { Err: contents };
+}
+export function isOk(item) {
+    return item != null && "Ok" in item;
+}
+export function isErr(item) {
+    return item != null && "Err" in item;
+}
+export const Result = {
+    apply(fns) {
+        const unexpected = "Unexpected Enum variant for Result<O, E>";
+        return function matchResultApply(item) {
+            return "Ok" in item
+                ? fns.Ok(item.Ok)
+                : "Err" in item
+                ? fns.Err(item.Err)
+                : console.assert(false, unexpected, item);
+        };
+    },
+    match(value, fns) {
+        return Result.apply(fns)(value);
+    },
+};
+"###);
+        assert_display_snapshot!(generate_declarations(parse(source), &Options::default()), @r###"
+export type Result<O, E> = { Ok: Ok<O> } | { Err: Err<E> };
+export type Ok<O> = O;
+export type Err<E> = E;
+export function Ok<O>(contents: Ok<O>): { Ok: Ok<O> };
+export function Err<E>(contents: Err<E>): { Err: Err<E> };
+export function isOk<O, E>(item: Result<O, E>): item is { Ok: Ok<O> };
+export function isErr<O, E>(item: Result<O, E>): item is { Err: Err<E> };
+export namespace Result {
+    export function apply<O, E, R>(fns: {
+        Ok(content: Ok<O>): R;
+        Err(content: Err<E>): R;
+    }): (value: Result<O, E>) => R;
+    export function match<O, E, R>(
+        value: Result<O, E>,
+        fns: {
+            Ok(content: Ok<O>): R;
+            Err(content: Err<E>): R;
+        }
+    ): R;
+}
+"###);
+    }
+
+    #[test]
+    fn generate_without_namespaces() {
+        assert_display_snapshot!(generate(parse(
+            r###"
+// enum-ts: members=object
+type Result<O, E> = Enum<{
+    Ok: O;
+    Err: E;
+}>;
+
+// enum-ts: members=functions
+export type Stoplight = Enum<{
+    Green: null;
+    Red: null;
+}>;
+            "###,
+        )), @r###"
+type Ok<O> = O;
+type Err<E> = E;
+function Ok<O>(contents: Ok<O>): { Ok: Ok<O> } {
+    return { Ok: contents };
+}
+function Err<E>(contents: Err<E>): { Err: Err<E> } {
+    return { Err: contents };
+}
+function isOk<O, E>(item: Result<O, E>): item is { Ok: Ok<O> } {
+    return item != null && "Ok" in item;
+}
+function isErr<O, E>(item: Result<O, E>): item is { Err: Err<E> } {
+    return item != null && "Err" in item;
+}
+const Result = {
+    apply<O, E, R>(fns: {
+        Ok(content: Ok<O>): R;
+        Err(content: Err<E>): R;
+    }): (value: Result<O, E>) => R {
+        const unexpected = "Unexpected Enum variant for Result<O, E>";
+        return function matchResultApply(item) {
+            return "Ok" in item
+                ? fns.Ok(item.Ok)
+                : "Err" in item
+                ? fns.Err(item.Err)
+                : (console.assert(false, unexpected, item) as never);
+        };
+    },
+    match<O, E, R>(
+        value: Result<O, E>,
+        fns: {
+            Ok(content: Ok<O>): R;
+            Err(content: Err<E>): R;
+        }
+    ): R {
+        return Result.apply(fns)(value);
+    },
+} as const;
+
+export type Green = null;
+export type Red = null;
+export function Green(): { Green: Green } {
+    return { Green: null };
+}
+export function Red(): { Red: Red } {
+    return { Red: null };
+}
+export function isGreen(item: Stoplight): item is { Green: Green } {
+    return item != null && "Green" in item;
+}
+export function isRed(item: Stoplight): item is { Red: Red } {
+    return item != null && "Red" in item;
+}
+export function applyStoplight<R>(fns: {
+    Green(): R;
+    Red(): R;
+}): (value: Stoplight) => R {
+    const unexpected = "Unexpected Enum variant for Stoplight";
+    return function matchStoplightApply(item) {
+        return "Green" in item
+            ? fns.Green()
+            : "Red" in item
+            ? fns.Red()
+            : (console.assert(false, unexpected, item) as never);
+    };
+}
+export function matchStoplight<R>(
+    value: Stoplight,
+    fns: {
+        Green(): R;
+        Red(): R;
+    }
+): R {
+    return applyStoplight(fns)(value);
+}
+"###)
+    }
+
+    #[test]
+    fn generate_unreachable_throw() {
+        let mut options = Options::default();
+        options.apply_directive("unreachable=throw").unwrap();
+        assert_display_snapshot!(generate_with(parse(
+            r###"
+export type Stoplight = Enum<{
+    Green: null;
+    Red: null;
+}>;
+
+// enum-ts: unreachable=handler:reportUnexpected
+type Unit = Enum<{
+    Nothing: null;
+}>;
+            "###,
+        ), &options), @r###"
+export type Green = null;
+export type Red = null;
+export function Green(): { Green: Green } {
+    return { Green: null };
+}
+export function Red(): { Red: Red } {
+    return { Red: null };
+}
+export function isGreen(item: Stoplight): item is { Green: Green } {
+    return item != null && "Green" in item;
+}
+export function isRed(item: Stoplight): item is { Red: Red } {
+    return item != null && "Red" in item;
+}
+export namespace Stoplight {
+    export function apply<R>(fns: {
+        Green(): R;
+        Red(): R;
+    }): (value: Stoplight) => R {
+        return function matchStoplightApply(item) {
+            return "Green" in item
+                ? fns.Green()
+                : "Red" in item
+                ? fns.Red()
+                : enumTsThrowUnexpected("Stoplight", item);
+        };
+    }
+    export function match<R>(
+        value: Stoplight,
+        fns: {
+            Green(): R;
+            Red(): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+
+type Nothing = null;
+function Nothing(): { Nothing: Nothing } {
+    return { Nothing: null };
+}
+function isNothing(item: Unit): item is { Nothing: Nothing } {
+    return item != null && "Nothing" in item;
+}
+namespace Unit {
+    export function apply<R>(fns: {
+        Nothing(): R;
+    }): (value: Unit) => R {
+        return function matchUnitApply(item) {
+            return "Nothing" in item
+                ? fns.Nothing()
+                : (reportUnexpected("Unit", item) as never);
+        };
+    }
+    export function match<R>(
+        value: Unit,
+        fns: {
+            Nothing(): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+
+import { UnexpectedVariantError as EnumTsUnexpectedVariantError } from "enum-ts-lib";
+function enumTsThrowUnexpected(enumName: string, value: unknown): never {
+    throw new EnumTsUnexpectedVariantError(enumName, value);
+}
+"###)
+    }
+
+    #[test]
+    fn generate_constrained_generics() {
+        assert_display_snapshot!(generate(parse(
+            r###"
+export type Result<O extends object, E = Error> = Enum<{
+    Ok: O;
+    Err: E;
+}>;
+            "###,
+        )), @r###"
+export type Ok<O extends object> = O;
+export type Err<E = Error> = E;
+export function Ok<O extends object>(contents: Ok<O>): { Ok: Ok<O> } {
+    return { Ok: contents };
+}
+export function Err<E>(contents: Err<E>): { Err: Err<E> } {
+    return { Err: contents };
+}
+export function isOk<O extends object, E>(item: Result<O, E>): item is { Ok: Ok<O> } {
+    return item != null && "Ok" in item;
+}
+export function isErr<O extends object, E>(item: Result<O, E>): item is { Err: Err<E> } {
+    return item != null && "Err" in item;
+}
+export namespace Result {
+    const unexpected = "Unexpected Enum variant for Result<O, E>";
+    export function apply<O extends object, E, R>(fns: {
+        Ok(content: Ok<O>): R;
+        Err(content: Err<E>): R;
+    }): (value: Result<O, E>) => R {
+        return function matchResultApply(item) {
+            return "Ok" in item
+                ? fns.Ok(item.Ok)
+                : "Err" in item
+                ? fns.Err(item.Err)
+                : (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<O extends object, E, R>(
+        value: Result<O, E>,
+        fns: {
+            Ok(content: Ok<O>): R;
+            Err(content: Err<E>): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+"###);
+    }
+
+    #[test]
+    fn generate_enum_creators() {
+        assert_display_snapshot!(generate(parse(
+            r###"
+// enum-ts: creators=enum
+type Result<O, E> = Enum<{
+    Ok: O;
+    Err: E;
+    Pending: null;
+}>;
+            "###,
+        )), @r###"
+type Ok<O> = O;
+type Err<E> = E;
+type Pending = null;
+function Ok<O>(contents: Ok<O>): Result<O, never> {
+    return { Ok: contents };
+}
+function Err<E>(contents: Err<E>): Result<never, E> {
+    return { Err: contents };
+}
+function Pending(): Result<never, never> {
+    return { Pending: null };
+}
+function isOk<O, E>(item: Result<O, E>): item is { Ok: Ok<O> } {
+    return item != null && "Ok" in item;
+}
+function isErr<O, E>(item: Result<O, E>): item is { Err: Err<E> } {
+    return item != null && "Err" in item;
+}
+function isPending<O, E>(item: Result<O, E>): item is { Pending: Pending } {
+    return item != null && "Pending" in item;
+}
+namespace Result {
+    const unexpected = "Unexpected Enum variant for Result<O, E>";
+    export function apply<O, E, R>(fns: {
+        Ok(content: Ok<O>): R;
+        Err(content: Err<E>): R;
+        Pending(): R;
+    }): (value: Result<O, E>) => R {
+        return function matchResultApply(item) {
+            return "Ok" in item
+                ? fns.Ok(item.Ok)
+                : "Err" in item
+                ? fns.Err(item.Err)
+                : "Pending" in item
+                ? fns.Pending()
+                : (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<O, E, R>(
+        value: Result<O, E>,
+        fns: {
+            Ok(content: Ok<O>): R;
+            Err(content: Err<E>): R;
+            Pending(): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+"###);
+    }
+
+    #[test]
+    fn generate_unit_variant_constants() {
+        let source = r###"
+// enum-ts: constants
+export type Stoplight = Enum<{
+    Green: 0;
+    Yellow: true;
+    Red: void;
+    Broken: { since: Date };
+}>;
+            "###;
+        assert_display_snapshot!(generate(parse(source)), @r###"
+export type Green = 0;
+export type Yellow = true;
+export type Red = void;
+export type Broken = { since: Date };
+export function Green(): { Green: Green } {
+    return { Green: 0 };
+}
+export function Yellow(): { Yellow: Yellow } {
+    return { Yellow: true };
+}
+export function Red(): { Red: Red } {
+    return { Red: undefined };
+}
+export function Broken(contents: Broken): { Broken: Broken } {
+    return { Broken: contents };
+}
+export function isGreen(item: Stoplight): item is { Green: Green } {
+    return item != null && "Green" in item;
+}
+export function isYellow(item: Stoplight): item is { Yellow: Yellow } {
+    return item != null && "Yellow" in item;
+}
+export function isRed(item: Stoplight): item is { Red: Red } {
+    return item != null && "Red" in item;
+}
+export function isBroken(item: Stoplight): item is { Broken: Broken } {
+    return item != null && "Broken" in item;
+}
+export namespace Stoplight {
+    export const Green: { Green: Green } = { Green: 0 };
+    export const Yellow: { Yellow: Yellow } = { Yellow: true };
+    export const Red: { Red: Red } = { Red: undefined };
+    const unexpected = "Unexpected Enum variant for Stoplight";
+    export function apply<R>(fns: {
+        Green(): R;
+        Yellow(): R;
+        Red(): R;
+        Broken(content: Broken): R;
+    }): (value: Stoplight) => R {
+        return function matchStoplightApply(item) {
+            return "Green" in item
+                ? fns.Green()
+                : "Yellow" in item
+                ? fns.Yellow()
+                : "Red" in item
+                ? fns.Red()
+                : "Broken" in item
+                ? fns.Broken(item.Broken)
+                : (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<R>(
+        value: Stoplight,
+        fns: {
+            Green(): R;
+            Yellow(): R;
+            Red(): R;
+            Broken(content: Broken): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+"###);
+        let options = Options {
+            members: Members::Functions,
+            ..Options::default()
+        };
+        assert_display_snapshot!(generate_js(parse(source), &options), @r###"
+export function Green() {
+    return { Green: 0 };
+}
+export function Yellow() {
+    return { Yellow: true };
+}
+export function Red() {
+    return { Red: undefined };
+}
+export function Broken(contents) {
+    return { Broken: contents };
+}
+export function isGreen(item) {
+    return item != null && "Green" in item;
+}
+export function isYellow(item) {
+    return item != null && "Yellow" in item;
+}
+export function isRed(item) {
+    return item != null && "Red" in item;
+}
+export function isBroken(item) {
+    return item != null && "Broken" in item;
+}
+export const GreenStoplight = { Green: 0 };
+export const YellowStoplight = { Yellow: true };
+export const RedStoplight = { Red: undefined };
+export function applyStoplight(fns) {
+    const unexpected = "Unexpected Enum variant for Stoplight";
+    return function matchStoplightApply(item) {
+        return "Green" in item
+            ? fns.Green()
+            : "Yellow" in item
+            ? fns.Yellow()
+            : "Red" in item
+            ? fns.Red()
+            : "Broken" in item
+            ? fns.Broken(item.Broken)
+            : console.assert(false, unexpected, item);
+    };
+}
+export function matchStoplight(value, fns) {
+    return applyStoplight(fns)(value);
+}
+"###);
+    }
+
+    #[test]
+    fn generate_tuple_variants() {
+        assert_display_snapshot!(generate(parse(
+            r###"
+type Shape<T> = Enum<{
+    Point: [x: number, y: number];
+    Tagged: [T, ...labels: string[]];
+    Path: [number, number][];
+}>;
+            "###,
+        )), @r###"
+type Point = [x: number, y: number];
+type Tagged<T> = [T, ...labels: string[]];
+type Path = [number, number][];
+function Point(...contents: Point): { Point: Point } {
+    return { Point: contents };
+}
+function Tagged<T>(...contents: Tagged<T>): { Tagged: Tagged<T> } {
+    return { Tagged: contents };
+}
+function Path(contents: Path): { Path: Path } {
+    return { Path: contents };
+}
+function isPoint<T>(item: Shape<T>): item is { Point: Point } {
+    return item != null && "Point" in item;
+}
+function isTagged<T>(item: Shape<T>): item is { Tagged: Tagged<T> } {
+    return item != null && "Tagged" in item;
+}
+function isPath<T>(item: Shape<T>): item is { Path: Path } {
+    return item != null && "Path" in item;
+}
+namespace Shape {
+    const unexpected = "Unexpected Enum variant for Shape<T>";
+    export function apply<T, R>(fns: {
+        Point(...content: Point): R;
+        Tagged(...content: Tagged<T>): R;
+        Path(content: Path): R;
+    }): (value: Shape<T>) => R {
+        return function matchShapeApply(item) {
+            return "Point" in item
+                ? fns.Point(...item.Point)
+                : "Tagged" in item
+                ? fns.Tagged(...item.Tagged)
+                : "Path" in item
+                ? fns.Path(item.Path)
+                : (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<T, R>(
+        value: Shape<T>,
+        fns: {
+            Point(...content: Point): R;
+            Tagged(...content: Tagged<T>): R;
+            Path(content: Path): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+"###);
+    }
+
+    #[test]
+    fn generate_doc_comments() {
+        assert_display_snapshot!(generate(parse(
+            r###"
+/** The outcome of a request */
+// enum-ts: constants
+export type Response = Enum<{
+    /**
+     * The request succeeded
+     * @see https://example.com
+     */
+    Ok: { body: string };
+    /** The request timed out */
+    Timeout: null;
+    Error: string;
+}>;
+            "###,
+        )), @r###"
+/**
+ * The request succeeded
+ * @see https://example.com
+ */
+export type Ok = { body: string };
+/** The request timed out */
+export type Timeout = null;
+export type Error = string;
+/**
+ * The request succeeded
+ * @see https://example.com
+ */
+export function Ok(contents: Ok): { Ok: Ok } {
+    return { Ok: contents };
+}
+/** The request timed out */
+export function Timeout(): { Timeout: Timeout } {
+    return { Timeout: null };
+}
+export function Error(contents: Error): { Error: Error } {
+    return { Error: contents };
+}
+/**
+ * The request succeeded
+ * @see https://example.com
+ */
+export function isOk(item: Response): item is { Ok: Ok } {
+    return item != null && "Ok" in item;
+}
+/** The request timed out */
+export function isTimeout(item: Response): item is { Timeout: Timeout } {
+    return item != null && "Timeout" in item;
+}
+export function isError(item: Response): item is { Error: Error } {
+    return item != null && "Error" in item;
+}
+/** The outcome of a request */
+export namespace Response {
+    /** The request timed out */
+    export const Timeout: { Timeout: Timeout } = { Timeout: null };
+    const unexpected = "Unexpected Enum variant for Response";
+    export function apply<R>(fns: {
+        /**
+         * The request succeeded
+         * @see https://example.com
+         */
+        Ok(content: Ok): R;
+        /** The request timed out */
+        Timeout(): R;
+        Error(content: Error): R;
+    }): (value: Response) => R {
+        return function matchResponseApply(item) {
+            return "Ok" in item
+                ? fns.Ok(item.Ok)
+                : "Timeout" in item
+                ? fns.Timeout()
+                : "Error" in item
+                ? fns.Error(item.Error)
+                : (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<R>(
+        value: Response,
+        fns: {
+            /**
+             * The request succeeded
+             * @see https://example.com
+             */
+            Ok(content: Ok): R;
+            /** The request timed out */
+            Timeout(): R;
+            Error(content: Error): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+"###);
+    }
+
+    #[test]
+    fn generate_readonly_and_freeze() {
+        let source = r###"
+// enum-ts: readonly, freeze, constants
+export type Toggle<T> = Enum<{
+    On: T;
+    Off: null;
+}>;
+            "###;
+        assert_display_snapshot!(generate(parse(source)), @r###"
+export type On<T> = T;
+export type Off = null;
+export function On<T>(contents: On<T>): { readonly On: On<T> } {
+    return Object.freeze({ On: contents });
+}
+export function Off(): { readonly Off: Off } {
+    return Object.freeze({ Off: null });
+}
+export function isOn<T>(item: Toggle<T>): item is { readonly On: On<T> } {
+    return item != null && "On" in item;
+}
+export function isOff<T>(item: Toggle<T>): item is { readonly Off: Off } {
+    return item != null && "Off" in item;
+}
+export namespace Toggle {
+    export const Off: { readonly Off: Off } = Object.freeze({ Off: null });
+    const unexpected = "Unexpected Enum variant for Toggle<T>";
+    export function apply<T, R>(fns: {
+        On(content: On<T>): R;
+        Off(): R;
+    }): (value: Toggle<T>) => R {
+        return function matchToggleApply(item) {
+            return "On" in item
+                ? fns.On(item.On)
+                : "Off" in item
+                ? fns.Off()
+                : (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<T, R>(
+        value: Toggle<T>,
+        fns: {
+            On(content: On<T>): R;
+            Off(): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+}
+"###);
+        let options = Options {
+            members: Members::Object,
+            creators: Creators::Enum,
+            ..Options::default()
+        };
+        assert_display_snapshot!(generate_declarations(parse(source), &options), @r###"
+export type Toggle<T> = { readonly On: On<T> } | { readonly Off: Off };
+export type On<T> = T;
+export type Off = null;
+export function On<T>(contents: On<T>): Readonly<Toggle<T>>;
+export function Off(): Readonly<Toggle<never>>;
+export function isOn<T>(item: Toggle<T>): item is { readonly On: On<T> };
+export function isOff<T>(item: Toggle<T>): item is { readonly Off: Off };
+export namespace Toggle {
+    export const Off: { readonly Off: Off };
+    export function apply<T, R>(fns: {
+        On(content: On<T>): R;
+        Off(): R;
+    }): (value: Toggle<T>) => R;
+    export function match<T, R>(
+        value: Toggle<T>,
+        fns: {
+            On(content: On<T>): R;
+            Off(): R;
+        }
+    ): R;
+}
+"###);
+    }
+
+    #[test]
+    fn generate_quoted_variant_names() {
+        assert_display_snapshot!(generate(parse(
+            r###"
+// enum-ts: dispatch=switch, equals
+export type HttpError = Enum<{
+    "not-found": null;
+    "404": string;
+    'Teapot': null;
+}>;
+            "###,
+        )), @r###"
+export type NotFound = null;
+export type _404 = string;
+export type Teapot = null;
+export function NotFound(): { "not-found": NotFound } {
+    return { "not-found": null };
+}
+export function _404(contents: _404): { "404": _404 } {
+    return { "404": contents };
+}
+export function Teapot(): { Teapot: Teapot } {
+    return { Teapot: null };
+}
+export function isNotFound(item: HttpError): item is { "not-found": NotFound } {
+    return item != null && "not-found" in item;
+}
+export function is_404(item: HttpError): item is { "404": _404 } {
+    return item != null && "404" in item;
+}
+export function isTeapot(item: HttpError): item is { Teapot: Teapot } {
+    return item != null && "Teapot" in item;
+}
+export namespace HttpError {
+    const unexpected = "Unexpected Enum variant for HttpError";
+    export function apply<R>(fns: {
+        "not-found"(): R;
+        "404"(content: _404): R;
+        Teapot(): R;
+    }): (value: HttpError) => R {
+        return function matchHttpErrorApply(item) {
+            for (const key in item) {
+                switch (key) {
+                    case "not-found":
+                        return fns["not-found"]();
+                    case "404":
+                        return fns["404"]((item as { "404": _404 })["404"]);
+                    case "Teapot":
+                        return fns.Teapot();
+                }
+            }
+            return (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<R>(
+        value: HttpError,
+        fns: {
+            "not-found"(): R;
+            "404"(content: _404): R;
+            Teapot(): R;
+        }
+    ): R {
+        return apply(fns)(value);
+    }
+    export function equals(
+        a: HttpError,
+        b: HttpError,
+        eqFns: {
+            "not-found"?(a: NotFound, b: NotFound): boolean;
+            "404"?(a: _404, b: _404): boolean;
+            Teapot?(a: Teapot, b: Teapot): boolean;
+        } = {}
+    ): boolean {
+        if (a === b) return true;
+        if (a == null || b == null) return false;
+        return "not-found" in a
+            ? "not-found" in b && (eqFns["not-found"] ? eqFns["not-found"](a["not-found"], b["not-found"]) : enumTsDeepEqual(a["not-found"], b["not-found"]))
+            : "404" in a
+            ? "404" in b && (eqFns["404"] ? eqFns["404"](a["404"], b["404"]) : enumTsDeepEqual(a["404"], b["404"]))
+            : "Teapot" in a
+            ? "Teapot" in b && (eqFns.Teapot ? eqFns.Teapot(a.Teapot, b.Teapot) : enumTsDeepEqual(a.Teapot, b.Teapot))
+            : false;
+    }
+    export function hash(value: HttpError): number {
+        return "not-found" in value
+            ? enumTsHashCombine(enumTsHashString("not-found"), enumTsDeepHash(value["not-found"]))
+            : "404" in value
+            ? enumTsHashCombine(enumTsHashString("404"), enumTsDeepHash(value["404"]))
+            : "Teapot" in value
+            ? enumTsHashCombine(enumTsHashString("Teapot"), enumTsDeepHash(value.Teapot))
+            : 0;
+    }
+}
+
+function enumTsDeepEqual(a: unknown, b: unknown): boolean {
+    if (a === b || (a !== a && b !== b)) return true;
+    if (typeof a !== "object" || a === null) return false;
+    if (typeof b !== "object" || b === null) return false;
+    if (Array.isArray(a) !== Array.isArray(b)) return false;
+    const keys = Object.keys(a);
+    if (keys.length !== Object.keys(b).length) return false;
+    for (const key of keys) {
+        if (
+            !Object.prototype.hasOwnProperty.call(b, key) ||
+            !enumTsDeepEqual((a as any)[key], (b as any)[key])
+        )
+            return false;
+    }
+    return true;
+}
+function enumTsDeepHash(value: unknown): number {
+    if (typeof value !== "object" || value === null) {
+        return enumTsHashString(typeof value + String(value));
+    }
+    let hash = Array.isArray(value) ? 2 : 3;
+    for (const key of Object.keys(value).sort()) {
+        hash = enumTsHashCombine(hash, enumTsHashString(key));
+        hash = enumTsHashCombine(hash, enumTsDeepHash((value as any)[key]));
+    }
+    return hash;
+}
+function enumTsHashString(str: string): number {
+    let hash = 0;
+    for (let i = 0; i < str.length; i++) hash = enumTsHashCombine(hash, str.charCodeAt(i));
+    return hash;
+}
+function enumTsHashCombine(hash: number, next: number): number {
+    return (Math.imul(hash, 31) + next) | 0;
+}
+"###);
+    }
+
+    #[test]
+    fn generate_switch_dispatch() {
+        assert_display_snapshot!(generate(parse(
+            r###"
+// enum-ts: dispatch=switch
+type Result<O, E> = Enum<{
+    Ok: O;
+    Err: E;
+    Pending: null;
+}>;
+            "###,
+        )), @r###"
+type Ok<O> = O;
+type Err<E> = E;
+type Pending = null;
+function Ok<O>(contents: Ok<O>): { Ok: Ok<O> } {
+    return { Ok: contents };
+}
+function Err<E>(contents: Err<E>): { Err: Err<E> } {
+    return { Err: contents };
+}
+function Pending(): { Pending: Pending } {
+    return { Pending: null };
+}
+function isOk<O, E>(item: Result<O, E>): item is { Ok: Ok<O> } {
+    return item != null && "Ok" in item;
+}
+function isErr<O, E>(item: Result<O, E>): item is { Err: Err<E> } {
+    return item != null && "Err" in item;
+}
+function isPending<O, E>(item: Result<O, E>): item is { Pending: Pending } {
+    return item != null && "Pending" in item;
+}
+namespace Result {
+    const unexpected = "Unexpected Enum variant for Result<O, E>";
+    export function apply<O, E, R>(fns: {
+        Ok(content: Ok<O>): R;
+        Err(content: Err<E>): R;
+        Pending(): R;
+    }): (value: Result<O, E>) => R {
+        return function matchResultApply(item) {
+            for (const key in item) {
+                switch (key) {
+                    case "Ok":
+                        return fns.Ok((item as { Ok: Ok<O> }).Ok);
+                    case "Err":
+                        return fns.Err((item as { Err: Err<E> }).Err);
+                    case "Pending":
+                        return fns.Pending();
+                }
+            }
+            return (console.assert(false, unexpected, item) as never);
+        };
+    }
+    export function match<O, E, R>(
+        value: Result<O, E>,
+        fns: {
+            Ok(content: Ok<O>): R;
+            Err(content: Err<E>): R;
+            Pending(): R;
         }
     ): R {
         return apply(fns)(value);
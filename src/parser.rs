@@ -1,70 +1,365 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
 
-#[derive(Debug, Hash)]
+use crate::{find_top_level, split_top_level};
+
+#[derive(Clone, Debug, Hash)]
 pub struct TSEnum {
     // type name
     pub name: String,
-    // list of generics sans < > if there are generics
-    pub generics: Option<String>,
+    // type parameters between the < > if there are generics
+    pub generics: Vec<TypeParam>,
     // t & c pairs
     pub variants: Vec<(String, String)>,
     pub export: bool,
+    // options from a `// enum-ts: equals` comment directly above the declaration
+    pub directives: Vec<String>,
+    // `/** ... */` comment above the declaration
+    pub doc: Option<String>,
+    // t & doc pairs for the variants with a `/** ... */` comment above them
+    pub variant_docs: Vec<(String, String)>,
+}
+
+impl TSEnum {
+    /// The `/** ... */` comment above the variant, if any
+    pub fn variant_doc(&self, t_name: &str) -> Option<&str> {
+        self.variant_docs
+            .iter()
+            .find(|(name, _)| name == t_name)
+            .map(|(_, doc)| doc.as_str())
+    }
+}
+
+/// A type parameter like `T`, `T extends object`, or `E = Error`
+#[derive(Clone, Debug, Hash)]
+pub struct TypeParam {
+    pub name: String,
+    // text after `extends`
+    pub constraint: Option<String>,
+    // text after `=`
+    pub default: Option<String>,
+}
+
+/// How a variant's contents are passed to its creator and match arm
+#[derive(Debug, PartialEq)]
+pub enum Payload {
+    /// A type with a single value like `null` or `0`: no argument, and this value at runtime
+    Unit(&'static str),
+    /// A tuple like `[x: number, y: number]`, spread into positional arguments
+    Tuple,
+    /// Any other type, passed as one argument
+    Value,
+}
+
+impl Payload {
+    pub fn of(contents: &str) -> Payload {
+        match contents.trim() {
+            "null" => Payload::Unit("null"),
+            "undefined" | "void" => Payload::Unit("undefined"),
+            "0" => Payload::Unit("0"),
+            "true" => Payload::Unit("true"),
+            "{}" => Payload::Unit("{}"),
+            contents if is_tuple(contents) => Payload::Tuple,
+            _ => Payload::Value,
+        }
+    }
+
+    /// `"..."` to spread tuple contents into arguments
+    pub fn spread(&self) -> &'static str {
+        match self {
+            Payload::Tuple => "...",
+            _ => "",
+        }
+    }
+}
+
+/// Whether the whole type is one `[...]`, and not for example `[number, number][]`
+fn is_tuple(contents: &str) -> bool {
+    if !contents.starts_with('[') {
+        return false;
+    }
+    let mut depth = 0;
+    for (idx, chr) in contents.char_indices() {
+        match chr {
+            '[' | '(' | '{' | '<' => depth += 1,
+            ']' | ')' | '}' => depth -= 1,
+            // not the `>` of an arrow function
+            '>' if !contents[..idx].ends_with('=') => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return idx == contents.len() - 1;
+        }
+    }
+    false
 }
 
 // Only matches enums which are on the first level
 static RE_ENUM: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\n(?P<export>export\s+)?type[\s]+(?P<name>\w+)(?:<(?P<generics>[\w\s,]+)>)?\s*=\s*Enum<\{(?P<variants>[\s\S]+?)\n\}>").unwrap()
+    Regex::new(r"\n(?:[ \t]*//[ \t]*enum-ts:(?P<directives_before_doc>[^\n]*)\n)?(?:(?P<doc>/\*\*(?:[^*]|\*+[^*/])*\*+/)[ \t]*\n)?(?:[ \t]*//[ \t]*enum-ts:(?P<directives>[^\n]*)\n)?(?P<export>export\s+)?type[\s]+(?P<name>\w+)(?:<(?P<generics>[^;\n]+?)>)?\s*=\s*Enum<(?:\{(?P<variants>[\s\S]+?)\n\}|(?P<variants_ref>\w+)(?:<(?P<variants_args>[^;\n]*?)>)?)>").unwrap()
 });
-static RE_VARIANTS_INDENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\n([\t ]+)").unwrap());
+static RE_VARIANTS_INDENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\n([\t ]*)").unwrap());
+// used for the generated code when no enum's variants are indented
+static DEFAULT_INDENT: &str = "    ";
 // after normalized with indent
+// up to the start of the contents, which are found with `variant_contents_len`
 static RE_VARIANT: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\n(?P<name>\w+):\s*(?P<contents>[^\n;,]+(?:\n[ \t]+[\s\S]+?\n[\]\}>]+)?)").unwrap()
+    // a doc comment cannot contain `*/`, so it never spans more than the one comment
+    Regex::new(r#"\n(?:(?P<doc>/\*\*(?:[^*]|\*+[^*/])*\*+/)[ \t]*\n)?(?P<name>\w+|"[^"\n]*"|'[^'\n]*'):\s*"#).unwrap()
+});
+static RE_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?:^|\n)import\s+(?:type\s+)?\{(?P<names>[^}]*)\}\s*from\s*["'](?P<from>\.[^"'\n]*)["']"#,
+    )
+    .unwrap()
 });
 
-#[derive(Debug, Hash)]
+#[derive(Clone, Debug)]
 pub struct Parsed {
     pub enums: Vec<TSEnum>,
     pub indent: String,
+    /// Enums which could not be parsed, and were left out
+    pub errors: Vec<String>,
+    /// Problems which don't stop the enums from being generated
+    pub warnings: Vec<String>,
+    /// Enums left out because the type with their variants was not found, whose generated code
+    /// is kept as it is
+    pub unresolved: Vec<String>,
+}
+
+// the generated code only depends on the enums which were parsed
+impl Hash for Parsed {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.enums.hash(state);
+        self.indent.hash(state);
+    }
 }
 
 pub fn parse(source: &str) -> Parsed {
+    parse_with_imports(source, |_| None)
+}
+
+/// Like [`parse`], and `Enum<MsgVariants>` can refer to an interface or type literal imported
+/// from a relative module, whose source is looked up with `read_import("./msg")`.
+pub fn parse_with_imports(source: &str, read_import: impl Fn(&str) -> Option<String>) -> Parsed {
     let mut enums = Vec::new();
     let mut indent = String::new();
-    for cap in RE_ENUM.captures_iter(&source) {
-        let variants: &str = &cap["variants"];
-        let indent_match: &str = &RE_VARIANTS_INDENT
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let mut unresolved = Vec::new();
+    for cap in RE_ENUM.captures_iter(source) {
+        let generics = cap
+            .name("generics")
+            .map_or_else(Vec::new, |val| parse_type_params(val.as_str()));
+        let variants = match (cap.name("variants"), cap.name("variants_ref")) {
+            (Some(variants), _) => variants.as_str().to_string(),
+            (None, Some(variants_ref)) => {
+                let (variants, params) = match find_variants(
+                    source,
+                    variants_ref.as_str(),
+                    &read_import,
+                ) {
+                    Some(found) => found,
+                    None => {
+                        warnings.push(format!(
+                                "enum {}: `{}` was not found in the file or a relative import, so its generated code is not updated",
+                                &cap["name"],
+                                variants_ref.as_str()
+                            ));
+                        unresolved.push(cap["name"].to_string());
+                        continue;
+                    }
+                };
+                // the variants refer to the type parameters by the enum's names
+                let names: Vec<&str> = generics.iter().map(|param| param.name.as_str()).collect();
+                let args = cap.name("variants_args").map_or("", |args| args.as_str());
+                let args: Vec<&str> = split_top_level(args, ',')
+                    .into_iter()
+                    .map(str::trim)
+                    .filter(|arg| !arg.is_empty())
+                    .collect();
+                let params: Vec<&str> = params.iter().map(String::as_str).collect();
+                if args != names || params != names {
+                    warnings.push(format!(
+                        "enum {}: the type parameters of `{}` must have the same names as the enum's, <{}>",
+                        &cap["name"],
+                        variants_ref.as_str(),
+                        names.join(", ")
+                    ));
+                }
+                variants
+            }
+            (None, None) => unreachable!("enum matched without variants"),
+        };
+        let indent_match = RE_VARIANTS_INDENT
             .captures(&variants)
-            .expect("at least one variant + indented")[1];
+            .map_or("", |cap| cap.get(1).unwrap().as_str());
+        let irregular = variants
+            .lines()
+            .find(|line| !line.trim().is_empty() && !line.starts_with(indent_match));
+        if let Some(line) = irregular {
+            errors.push(format!(
+                "enum {}: line indentation is irregular: `{}`",
+                &cap["name"],
+                line.trim()
+            ));
+            continue;
+        }
         let unindented_variants: String = variants
             .lines()
-            .into_iter()
-            .map(|line| {
-                let unindented = line.replacen(&indent_match, "", 1);
-                assert!(
-                    line.is_empty() || unindented != line,
-                    "line indentation is irregular:>>>{}<<<",
-                    line
-                );
-                unindented
-            })
-            .collect::<Vec<String>>()
+            .map(|line| line.get(indent_match.len()..).unwrap_or_default())
+            .collect::<Vec<&str>>()
             .join("\n");
-        indent = indent_match.to_string();
+        if !indent_match.is_empty() {
+            indent = indent_match.to_string();
+        }
+        let variants = parse_variants(&unindented_variants);
 
         enums.push(TSEnum {
             name: cap["name"].to_string(),
-            generics: cap.name("generics").map(|val| val.as_str().to_string()),
+            generics,
             export: cap.name("export").is_some(),
-            variants: RE_VARIANT
-                .captures_iter(&unindented_variants)
-                .map(|cap| (cap["name"].to_string(), cap["contents"].to_string()))
+            // the `// enum-ts:` comment can be above or below the `/** ... */` comment
+            directives: ["directives_before_doc", "directives"]
+                .iter()
+                .filter_map(|group| cap.name(group))
+                .flat_map(|directives| directives.as_str().split(','))
+                .map(str::trim)
+                .filter(|directive| !directive.is_empty())
+                .map(String::from)
                 .collect(),
+            doc: cap.name("doc").map(|doc| doc.as_str().to_string()),
+            variant_docs: variants
+                .iter()
+                .filter_map(|(name, _, doc)| Some((name.clone(), doc.clone()?)))
+                .collect(),
+            variants: variants
+                .into_iter()
+                .map(|(name, contents, _)| (name, contents))
+                .collect(),
+        });
+    }
+
+    if indent.is_empty() && !enums.is_empty() {
+        indent = DEFAULT_INDENT.to_string();
+    }
+    Parsed {
+        indent,
+        enums,
+        errors,
+        warnings,
+        unresolved,
+    }
+}
+
+/// The body and type parameter names of `interface MsgVariants { ... }` or
+/// `type MsgVariants = { ... }` declared in the source, or in the relative module it is
+/// imported from
+fn find_variants(
+    source: &str,
+    type_name: &str,
+    read_import: &dyn Fn(&str) -> Option<String>,
+) -> Option<(String, Vec<String>)> {
+    let re_declaration = Regex::new(&format!(
+        r"(?:^|\n)(?:export\s+)?(?:interface\s+{name}(?:<(?P<params>[^{{\n]*)>)?\s*|type\s+{name}(?:<(?P<type_params>[^=\n]*)>)?\s*=\s*)\{{(?P<body>[\s\S]+?)\n\}}",
+        name = regex::escape(type_name)
+    ))
+    .unwrap();
+    if let Some(cap) = re_declaration.captures(source) {
+        let params = cap
+            .name("params")
+            .or_else(|| cap.name("type_params"))
+            .map_or_else(Vec::new, |params| {
+                parse_type_params(params.as_str())
+                    .into_iter()
+                    .map(|param| param.name)
+                    .collect()
+            });
+        return Some((cap["body"].to_string(), params));
+    }
+    for cap in RE_IMPORT.captures_iter(source) {
+        // "MsgVariants" or "Variants as MsgVariants"
+        let imported_name = cap["names"].split(',').find_map(|name| {
+            let mut parts = name.split(" as ").map(str::trim);
+            let imported = parts.next()?;
+            let local = parts.next().unwrap_or(imported);
+            Some(imported).filter(|_| local == type_name)
         });
+        if let Some(imported_name) = imported_name {
+            // only looks one module deep, so imports cannot cycle
+            let imported_source = read_import(&cap["from"])?;
+            return find_variants(&imported_source, imported_name, &|_| None);
+        }
     }
+    None
+}
+
+/// The name, contents and doc comment of each variant in unindented `variants`
+fn parse_variants(variants: &str) -> Vec<(String, String, Option<String>)> {
+    let mut parsed = Vec::new();
+    let mut rest = variants;
+    while let Some(cap) = RE_VARIANT.captures(rest) {
+        let start = cap.get(0).unwrap().end();
+        let len = variant_contents_len(&rest[start..]);
+        let contents = rest[start..start + len].trim_end();
+        if !contents.is_empty() {
+            parsed.push((
+                variant_name(&cap["name"]),
+                contents.to_string(),
+                cap.name("doc").map(|doc| doc.as_str().to_string()),
+            ));
+        }
+        rest = &rest[start + len..];
+    }
+    parsed
+}
 
-    Parsed { indent, enums }
+/// The length of a variant's contents, up to the `;`, `,` or end of the line which is not nested
+/// in brackets, so `[a: string[], b: number]` and `{` continued on the next lines are kept whole
+fn variant_contents_len(contents: &str) -> usize {
+    [";", ",", "\n"]
+        .iter()
+        .filter_map(|end| find_top_level(contents, end, ""))
+        .min()
+        .unwrap_or(contents.len())
+}
+
+/// `not-found` for a quoted `"not-found"` variant, which keeps its key at runtime
+fn variant_name(name: &str) -> String {
+    ['"', '\'']
+        .iter()
+        .find_map(|&quote| name.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(name)
+        .to_string()
+}
+
+/// Parses `"T extends object, E = Error"` into its type parameters
+fn parse_type_params(generics: &str) -> Vec<TypeParam> {
+    split_top_level(generics, ',')
+        .into_iter()
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            // "E = Error", but not the `=>` of a function type
+            let (param, default) = match find_top_level(param, "=", "=>") {
+                Some(idx) => (&param[..idx], Some(param[idx + 1..].trim().to_string())),
+                None => (param, None),
+            };
+            let (name, constraint) = match find_top_level(param, " extends ", "") {
+                Some(idx) => (
+                    &param[..idx],
+                    Some(param[idx + " extends ".len()..].trim().to_string()),
+                ),
+                None => (param, None),
+            };
+            TypeParam {
+                name: name.trim().to_string(),
+                constraint,
+                default,
+            }
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -94,9 +389,18 @@ type Stoplight = Enum<{
             enums: [
                 TSEnum {
                     name: "Result",
-                    generics: Some(
-                        "Ok, Err",
-                    ),
+                    generics: [
+                        TypeParam {
+                            name: "Ok",
+                            constraint: None,
+                            default: None,
+                        },
+                        TypeParam {
+                            name: "Err",
+                            constraint: None,
+                            default: None,
+                        },
+                    ],
                     variants: [
                         (
                             "Ok",
@@ -108,10 +412,13 @@ type Stoplight = Enum<{
                         ),
                     ],
                     export: false,
+                    directives: [],
+                    doc: None,
+                    variant_docs: [],
                 },
                 TSEnum {
                     name: "Stoplight",
-                    generics: None,
+                    generics: [],
                     variants: [
                         (
                             "Green",
@@ -127,10 +434,229 @@ type Stoplight = Enum<{
                         ),
                     ],
                     export: false,
+                    directives: [],
+                    doc: None,
+                    variant_docs: [],
                 },
             ],
             indent: "    ",
+            errors: [],
+            warnings: [],
+            unresolved: [],
         }
         "###)
     }
+
+    #[test]
+    fn parse_directives() {
+        let parsed = parse(
+            r###"
+// enum-ts: equals, unknown-option
+export type Stoplight = Enum<{
+    Green: 0;
+}>;
+type Unit = Enum<{
+    Unit: null;
+}>;
+            "###,
+        );
+        assert_eq!(parsed.enums[0].directives, vec!["equals", "unknown-option"]);
+        assert!(parsed.enums[1].directives.is_empty());
+    }
+
+    #[test]
+    fn parse_directives_around_doc() {
+        let parsed = parse(
+            r###"
+// enum-ts: equals
+/** Traffic lights */
+type Stoplight = Enum<{
+    Green: 0;
+}>;
+/** Nothing */
+// enum-ts: constants
+type Unit = Enum<{
+    None: null;
+}>;
+            "###,
+        );
+        assert_eq!(parsed.enums[0].directives, vec!["equals"]);
+        assert_eq!(
+            parsed.enums[0].doc.as_deref(),
+            Some("/** Traffic lights */")
+        );
+        assert_eq!(parsed.enums[1].directives, vec!["constants"]);
+        assert_eq!(parsed.enums[1].doc.as_deref(), Some("/** Nothing */"));
+    }
+
+    #[test]
+    fn parse_referenced_variants() {
+        let parsed = parse_with_imports(
+            r###"
+import { Ack as AckVariants } from "./ack";
+
+interface MsgVariants {
+    Ping: null;
+    Text: string;
+}
+
+export type Msg = Enum<MsgVariants>;
+type Ack = Enum<AckVariants>;
+type Missing = Enum<Unknown>;
+            "###,
+            |specifier| {
+                assert_eq!(specifier, "./ack");
+                Some("export type Ack = {\n  Received: number;\n};\n".to_string())
+            },
+        );
+        let variants: Vec<_> = parsed
+            .enums
+            .iter()
+            .map(|ts_enum| (ts_enum.name.as_str(), &ts_enum.variants))
+            .collect();
+        assert_debug_snapshot!(variants, @r###"
+        [
+            (
+                "Msg",
+                [
+                    (
+                        "Ping",
+                        "null",
+                    ),
+                    (
+                        "Text",
+                        "string",
+                    ),
+                ],
+            ),
+            (
+                "Ack",
+                [
+                    (
+                        "Received",
+                        "number",
+                    ),
+                ],
+            ),
+        ]
+        "###);
+        assert_eq!(parsed.unresolved, vec!["Missing"]);
+        assert_eq!(
+            parsed.warnings,
+            vec!["enum Missing: `Unknown` was not found in the file or a relative import, so its generated code is not updated"]
+        );
+    }
+
+    #[test]
+    fn parse_referenced_variants_generics() {
+        let parsed = parse(
+            r###"
+interface ResultVariants<O, E> {
+Ok: O;
+Err: E;
+}
+
+type Result<O, E> = Enum<ResultVariants<O, E>>;
+type Renamed<T, E> = Enum<ResultVariants<T, E>>;
+            "###,
+        );
+        let variants: Vec<_> = parsed.enums.iter().map(|e| &e.variants).collect();
+        assert_eq!(
+            variants[0],
+            &vec![("Ok".into(), "O".into()), ("Err".into(), "E".into())]
+        );
+        assert_eq!(
+            parsed.warnings,
+            vec!["enum Renamed: the type parameters of `ResultVariants` must have the same names as the enum's, <T, E>"]
+        );
+    }
+
+    #[test]
+    fn parse_nested_brackets_in_contents() {
+        let parsed = parse(
+            r###"
+type Action = Enum<{
+    Move: [a: string[], b: number];
+    Pairs: [Array<[number, number]>, { x: [1, 2] }],
+    Callback: (done: () => void) => void;
+    Options: {
+        tags: string[];
+    };
+}>;
+            "###,
+        );
+        assert_debug_snapshot!(parsed.enums[0].variants, @r###"
+        [
+            (
+                "Move",
+                "[a: string[], b: number]",
+            ),
+            (
+                "Pairs",
+                "[Array<[number, number]>, { x: [1, 2] }]",
+            ),
+            (
+                "Callback",
+                "(done: () => void) => void",
+            ),
+            (
+                "Options",
+                "{\n    tags: string[];\n}",
+            ),
+        ]
+        "###);
+    }
+
+    #[test]
+    fn variant_names_lose_one_pair_of_quotes() {
+        assert_eq!(variant_name("Ok"), "Ok");
+        assert_eq!(variant_name("\"not-found\""), "not-found");
+        assert_eq!(variant_name("'not-found'"), "not-found");
+        assert_eq!(variant_name("\"'a'\""), "'a'");
+        assert_eq!(variant_name("'\"a\"'"), "\"a\"");
+    }
+
+    #[test]
+    fn payload_kinds() {
+        assert_eq!(Payload::of("null"), Payload::Unit("null"));
+        assert_eq!(Payload::of("void"), Payload::Unit("undefined"));
+        assert_eq!(Payload::of(" 0"), Payload::Unit("0"));
+        assert_eq!(Payload::of("{}"), Payload::Unit("{}"));
+        assert_eq!(Payload::of("{ id: string }"), Payload::Value);
+        assert_eq!(Payload::of("null | string"), Payload::Value);
+        assert_eq!(Payload::of("[x: number, y?: number]"), Payload::Tuple);
+        assert_eq!(Payload::of("[Array<T>, (a: T) => void]"), Payload::Tuple);
+        assert_eq!(Payload::of("[number, number][]"), Payload::Value);
+    }
+
+    #[test]
+    fn parse_type_param_constraints_and_defaults() {
+        let parsed = parse(
+            r###"
+type Tree<T extends Record<string, unknown> = {}, F = (a: T) => void> = Enum<{
+    Leaf: T;
+}>;
+            "###,
+        );
+        assert_debug_snapshot!(parsed.enums[0].generics, @r###"
+        [
+            TypeParam {
+                name: "T",
+                constraint: Some(
+                    "Record<string, unknown>",
+                ),
+                default: Some(
+                    "{}",
+                ),
+            },
+            TypeParam {
+                name: "F",
+                constraint: None,
+                default: Some(
+                    "(a: T) => void",
+                ),
+            },
+        ]
+        "###);
+    }
 }
@@ -0,0 +1,279 @@
+//! What write mode prints for each file and at the end of a run
+use crate::*;
+
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// What to do with a file whose generated code is out of date
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    /// Write the updated file
+    Write,
+    /// Print the updated file without writing it
+    DryRun,
+    /// Print only the path of the file
+    Check,
+    /// Print a unified diff of the update
+    Diff,
+}
+
+impl Action {
+    /// Whether out of date files are written
+    pub fn writes(self) -> bool {
+        self == Action::Write
+    }
+
+    /// Prints a change as it is made, for `--format text`
+    pub fn print(self, change: &Change) {
+        let path_str = change.path.to_string_lossy();
+        let (done, dry_run) = match change.kind {
+            ChangeKind::Update => ("Wrote", "Would write"),
+            ChangeKind::RemoveRegion => ("Removed region", "Would remove region"),
+            ChangeKind::Delete => ("Deleted", "Would delete"),
+        };
+        match (self, change.kind) {
+            (Action::Write, _) => println!("{}: {}", done, path_str),
+            (Action::DryRun, ChangeKind::Delete) => println!("{}: {}", dry_run, path_str),
+            (Action::DryRun, _) => println!(
+                "{}: {}\n<<enum-ts-dry-run>>\n{}\n<</enum-ts-dry-run>>",
+                dry_run, path_str, change.contents,
+            ),
+            (Action::Check, ChangeKind::RemoveRegion) => {
+                println!("Region without enums: {}", path_str)
+            }
+            (Action::Check, _) => println!("Out of date: {}", path_str),
+            (Action::Diff, _) => print!("{}", diff(change)),
+        }
+    }
+}
+
+fn diff(change: &Change) -> String {
+    let path_str = change.path.to_string_lossy();
+    let old_label = if change.existing.is_some() {
+        &path_str
+    } else {
+        "/dev/null"
+    };
+    let new_label = if change.kind == ChangeKind::Delete {
+        "/dev/null"
+    } else {
+        &path_str
+    };
+    unified_diff(
+        old_label,
+        new_label,
+        change.existing.as_deref().unwrap_or(""),
+        &change.contents,
+    )
+}
+
+/// What happened to one walked file, or the error walking or updating it
+#[derive(Debug)]
+pub struct FileResult {
+    pub path: PathBuf,
+    pub update: Result<FileUpdate, String>,
+}
+
+/// The results of updating a tree of files
+#[derive(Debug)]
+pub struct Report {
+    pub action: Action,
+    /// Whether the generated code was removed rather than updated
+    pub clean: bool,
+    pub results: Vec<FileResult>,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    fn updates(&self) -> impl Iterator<Item = &FileUpdate> {
+        self.results
+            .iter()
+            .filter_map(|result| result.update.as_ref().ok())
+    }
+
+    /// How the files with changes are described, ex "updated" or "out of date"
+    fn status(&self) -> &'static str {
+        match (self.clean, self.action.writes()) {
+            (false, true) => "updated",
+            (false, false) => "out of date",
+            (true, true) => "cleaned",
+            (true, false) => "to clean",
+        }
+    }
+
+    pub fn out_of_date(&self) -> usize {
+        self.updates()
+            .filter(|update| !update.changes.is_empty())
+            .count()
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = (&PathBuf, &str)> {
+        self.results.iter().flat_map(|result| {
+            let warnings = match &result.update {
+                Ok(update) => update.warnings.as_slice(),
+                Err(_) => &[],
+            };
+            warnings
+                .iter()
+                .map(move |warning| (&result.path, warning.as_str()))
+        })
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = (&PathBuf, &str)> {
+        self.results
+            .iter()
+            .filter_map(|result| match &result.update {
+                Err(err) => Some((&result.path, err.as_str())),
+                Ok(_) => None,
+            })
+    }
+
+    /// Up to date files which were skipped because they were unchanged
+    pub fn cached(&self) -> usize {
+        self.updates().filter(|update| update.cached).count()
+    }
+
+    /// ex "Scanned 3 files with 4 enums in 1.20ms: 1 updated, 1 up to date (1 cached), 1 error"
+    pub fn summary(&self) -> String {
+        let out_of_date = self.out_of_date();
+        let up_to_date = self.updates().count() - out_of_date;
+        let cached = match self.cached() {
+            0 => String::new(),
+            cached => format!(" ({} cached)", cached),
+        };
+        format!(
+            "Scanned {} with {} in {:.2?}: {} {}, {} up to date{}, {}",
+            plural(self.results.len(), "file"),
+            plural(self.updates().map(|update| update.enums).sum(), "enum"),
+            self.elapsed,
+            out_of_date,
+            self.status(),
+            up_to_date,
+            cached,
+            plural(self.errors().count(), "error"),
+        )
+    }
+
+    /// Per-file results and the summary counts, for `--format json`
+    pub fn to_json(&self) -> Value {
+        let files: Vec<Value> = self
+            .results
+            .iter()
+            .map(|result| match &result.update {
+                Ok(update) => {
+                    let status = match (update.changes.is_empty(), self.action.writes()) {
+                        (true, _) => "up-to-date",
+                        (false, true) => "updated",
+                        (false, false) => "out-of-date",
+                    };
+                    let mut file = json!({
+                        "path": result.path,
+                        "status": status,
+                        "enums": update.enums,
+                        "cached": update.cached,
+                        "warnings": update.warnings,
+                        "outputs": update.changes.iter().map(|change| json!({
+                            "path": change.path,
+                            "change": match change.kind {
+                                ChangeKind::Update => "update",
+                                ChangeKind::RemoveRegion => "remove-region",
+                                ChangeKind::Delete => "delete",
+                            },
+                        })).collect::<Vec<_>>(),
+                    });
+                    if self.action == Action::Diff {
+                        file["diff"] = update.changes.iter().map(diff).collect::<String>().into();
+                    }
+                    file
+                }
+                Err(err) => json!({ "path": result.path, "status": "error", "error": err }),
+            })
+            .collect();
+        let out_of_date = self.out_of_date();
+        json!({
+            "files": files,
+            "summary": {
+                "scanned": self.results.len(),
+                "enums": self.updates().map(|update| update.enums).sum::<usize>(),
+                "out_of_date": out_of_date,
+                "written": if self.action.writes() { out_of_date } else { 0 },
+                "up_to_date": self.updates().count() - out_of_date,
+                "cached": self.cached(),
+                "errors": self.errors().count(),
+                "elapsed_ms": self.elapsed.as_millis() as u64,
+            },
+        })
+    }
+
+    /// 2 when any file failed, 1 when `check` or `diff` found out of date files, otherwise 0
+    pub fn exit_code(&self) -> i32 {
+        if self.errors().next().is_some() {
+            2
+        } else if self.out_of_date() > 0 && matches!(self.action, Action::Check | Action::Diff) {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+/// ex "1 file" or "2 files"
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_results() {
+        let report = Report {
+            action: Action::Check,
+            clean: false,
+            results: vec![
+                FileResult {
+                    path: PathBuf::from("a.ts"),
+                    update: Ok(FileUpdate {
+                        enums: 2,
+                        changes: vec![Change {
+                            path: PathBuf::from("a.ts"),
+                            kind: ChangeKind::Update,
+                            existing: Some(String::new()),
+                            contents: String::from("updated"),
+                        }],
+                        ..FileUpdate::default()
+                    }),
+                },
+                FileResult {
+                    path: PathBuf::from("b.ts"),
+                    update: Ok(FileUpdate {
+                        cached: true,
+                        ..FileUpdate::default()
+                    }),
+                },
+                FileResult {
+                    path: PathBuf::from("c.ts"),
+                    update: Err(String::from("permission denied")),
+                },
+            ],
+            elapsed: Duration::from_millis(12),
+        };
+        assert_eq!(
+            report.summary(),
+            "Scanned 3 files with 2 enums in 12.00ms: 1 out of date, 1 up to date (1 cached), 1 error"
+        );
+        assert_eq!(report.exit_code(), 2);
+        let json = report.to_json();
+        assert_eq!(json["files"][0]["status"], "out-of-date");
+        assert_eq!(json["files"][1]["status"], "up-to-date");
+        assert_eq!(json["files"][2]["error"], "permission denied");
+        assert_eq!(json["summary"]["written"], 0);
+        assert_eq!(json["summary"]["cached"], 1);
+    }
+}
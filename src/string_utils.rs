@@ -63,6 +63,103 @@ pub fn rstrip_slice<'a>(input: &'a str, strip: &str) -> &'a str {
     &input[..end]
 }
 
+/// Byte offset of the first `pattern` in `input` which is not nested in brackets,
+/// skipping matches where `unless` starts at the same offset (if `unless` is non-empty).
+pub fn find_top_level(input: &str, pattern: &str, unless: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (idx, chr) in input.char_indices() {
+        match chr {
+            '<' | '(' | '[' | '{' => depth += 1,
+            // `=>` is not a closing bracket
+            '>' if input[..idx].ends_with('=') => {}
+            '>' | ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if depth == 0
+            && input[idx..].starts_with(pattern)
+            && (unless.is_empty() || !input[idx..].starts_with(unless))
+        {
+            return Some(idx);
+        }
+    }
+    None
+}
+
+/// Split `input` on `separator`s which are not nested in brackets, so
+/// `"A, B<C, D>"` splits into `"A"` and `" B<C, D>"`.
+pub fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = input;
+    let mut separator_buf = [0u8; 4];
+    let separator: &str = separator.encode_utf8(&mut separator_buf);
+    while let Some(idx) = find_top_level(rest, separator, "") {
+        parts.push(&rest[..idx]);
+        rest = &rest[idx + separator.len()..];
+    }
+    parts.push(rest);
+    parts
+}
+
+/// A unified diff of `old` and `new` with three lines of context, treating the lines between
+/// the first and last changed line as a single hunk, or an empty string if they are the same.
+pub fn unified_diff(old_label: &str, new_label: &str, old: &str, new: &str) -> String {
+    const CONTEXT: usize = 3;
+    if old == new {
+        return String::new();
+    }
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let prefix = old_lines
+        .iter()
+        .zip(new_lines.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let start = prefix.saturating_sub(CONTEXT);
+    let old_end = (old_lines.len() - suffix + CONTEXT).min(old_lines.len());
+    let new_end = (new_lines.len() - suffix + CONTEXT).min(new_lines.len());
+    // an empty range is numbered by the line before it
+    let range = |len: usize| {
+        if len == 0 {
+            format!("{},0", start)
+        } else {
+            format!("{},{}", start + 1, len)
+        }
+    };
+    let mut diff = format!(
+        "--- {}\n+++ {}\n@@ -{} +{} @@\n",
+        old_label,
+        new_label,
+        range(old_end - start),
+        range(new_end - start)
+    );
+    let mut push_line = |marker: char, line: &str| {
+        diff.push(marker);
+        diff.push_str(line);
+        if !line.ends_with('\n') {
+            diff.push_str("\n\\ No newline at end of file\n");
+        }
+    };
+    for line in &old_lines[start..prefix] {
+        push_line(' ', line);
+    }
+    for line in &old_lines[prefix..old_lines.len() - suffix] {
+        push_line('-', line);
+    }
+    for line in &new_lines[prefix..new_lines.len() - suffix] {
+        push_line('+', line);
+    }
+    for line in &old_lines[old_lines.len() - suffix..old_end] {
+        push_line(' ', line);
+    }
+    diff
+}
+
 /// A position in a source file specified by a 1-indexed line number and a
 /// 0-indexed byte offset into the line specified by that number.
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -262,6 +359,47 @@ here
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_split_top_level() {
+        assert_eq!(split_top_level("A, B", ','), vec!["A", " B"]);
+        assert_eq!(split_top_level("A", ','), vec!["A"]);
+        assert_eq!(split_top_level("", ','), vec![""]);
+        assert_eq!(
+            split_top_level("A extends Map<K, V>, B = { a: 1, b: 2 }", ','),
+            vec!["A extends Map<K, V>", " B = { a: 1, b: 2 }"]
+        );
+        assert_eq!(
+            split_top_level("F extends (a: A, b: B) => C, D", ','),
+            vec!["F extends (a: A, b: B) => C", " D"]
+        );
+        assert_eq!(
+            find_top_level("F extends (a) => C = G", "=", "=>"),
+            Some(19)
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(unified_diff("a", "b", "same\n", "same\n"), "");
+        assert_eq!(
+            unified_diff(
+                "a.ts",
+                "a.ts",
+                "1\n2\n3\n4\n5\n6\n",
+                "1\n2\n3\n4\nfive\n6\n"
+            ),
+            "--- a.ts\n+++ a.ts\n@@ -2,5 +2,5 @@\n 2\n 3\n 4\n-5\n+five\n 6\n"
+        );
+        assert_eq!(
+            unified_diff("a.ts", "a.ts", "1", "1\n2\n"),
+            "--- a.ts\n+++ a.ts\n@@ -1,1 +1,2 @@\n-1\n\\ No newline at end of file\n+1\n+2\n"
+        );
+        assert_eq!(
+            unified_diff("/dev/null", "a.js", "", "x\n"),
+            "--- /dev/null\n+++ a.js\n@@ -0,0 +1,1 @@\n+x\n"
+        );
+    }
+
     #[test]
     fn test_lstrip_slice() {
         let examples = vec![
@@ -293,6 +431,7 @@ here
     }
 
     #[test]
+    #[allow(clippy::needless_borrow)]
     fn test_file_positions() {
         // Empty string has expected behavior
         let mut string_pos = StringPositions::new(&r#""#);
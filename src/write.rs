@@ -1,10 +1,17 @@
 use crate::*;
 
+use aho_corasick::AhoCorasick;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::fs;
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::hash::{Hash, Hasher};
-use std::{collections::hash_map::DefaultHasher, path::Path};
+use std::io::Write;
+use std::{
+    collections::hash_map::DefaultHasher,
+    path::{Path, PathBuf},
+};
+use std::{fs, io};
 
 // if the enum type structure ever updates, then increment this
 const ENUM_STRUCTURE_VERSION: usize = 2;
@@ -12,6 +19,15 @@ const ENUM_STRUCTURE_VERSION: usize = 2;
 static PREFIX_PRE_HASH: &str = "\n//#region enum-ts generated <";
 static PREFIX_POST_HASH: &str = ">\n";
 static SUFFIX: &str = "\n//#endregion";
+// First line of `.enum.js` and `.enum.d.ts` files generated next to the source
+static GENERATED_FILE_PREFIX: &str = "// enum-ts generated <";
+
+// `Enum<` or the start of a generated region, without which a file has nothing to update
+static RE_NEEDS_PARSE: Lazy<AhoCorasick> =
+    Lazy::new(|| AhoCorasick::new(["Enum<", PREFIX_PRE_HASH.trim_start()]));
+
+// `type Msg = Enum<`, but not `MyEnum<T>` or `Enum<` in the generated code
+static RE_ENUM_DECLARATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"=\s*Enum<").unwrap());
 
 static RE_ENUM_TS_REGION: Lazy<Regex> = Lazy::new(|| {
     let mut source = regex::escape(PREFIX_PRE_HASH);
@@ -23,80 +39,709 @@ static RE_ENUM_TS_REGION: Lazy<Regex> = Lazy::new(|| {
     Regex::new(&source).unwrap()
 });
 
-fn make_edit_offsets(contents: &str, force: bool) -> Option<(usize, usize, String)> {
-    let parsed = parse(contents);
-    if parsed.enums.is_empty() {
-        // no enums to generate
-        return None;
+// `import x from`, `export const`, `export {` and the like, but not a dynamic `import(`
+static RE_MODULE_STATEMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^[ \t]*(?:import[\s{*"']|export[\s{*])"#).unwrap());
+
+/// Warns when the `UnexpectedVariantError` import of `unreachable=throw` would be the only
+/// `import` or `export` of the file, turning a script into a module
+pub fn check_module(contents: &str, parsed: &mut Parsed, options: &Options) {
+    let throwing = parsed
+        .enums
+        .iter()
+        .find(|ts_enum| matches!(options.for_enum(ts_enum).unreachable, Unreachable::Throw(_)));
+    if let Some(ts_enum) = throwing {
+        if !RE_MODULE_STATEMENT.is_match(&RE_ENUM_TS_REGION.replace_all(contents, "")) {
+            let warning = format!(
+                "enum {}: `unreachable=throw` imports `UnexpectedVariantError` in the generated code, which makes this file a module as it has no other import or export",
+                ts_enum.name
+            );
+            parsed.warnings.push(warning);
+        }
     }
+}
+
+fn hash_parsed(parsed: &Parsed, options: &Options) -> String {
     let mut hasher = DefaultHasher::new();
     parsed.hash(&mut hasher);
     ENUM_STRUCTURE_VERSION.hash(&mut hasher);
     CODE_GEN_VERSION.hash(&mut hasher);
-    let hash_str = format!("{:x}", hasher.finish());
+    options.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+/// The byte range of `contents` to replace and what to replace it with to update the generated region
+pub fn make_edit_offsets(
+    contents: &str,
+    parsed: Parsed,
+    force: bool,
+    options: &Options,
+) -> Option<(usize, usize, String)> {
+    // the region still has the code of enums which were left out
+    if !parsed.unresolved.is_empty() || !parsed.errors.is_empty() {
+        return None;
+    }
+    if parsed.enums.is_empty() {
+        return remove_orphaned_region(contents);
+    }
+    let hash_str = hash_parsed(&parsed, options);
     let prefix: String = String::from(PREFIX_PRE_HASH) + &hash_str + PREFIX_POST_HASH;
     if !force && contents.contains(&prefix) {
         None
     } else {
         let mut to_write = prefix;
-        to_write.extend(generate(parsed).drain(..));
-        to_write.push_str(&SUFFIX);
+        to_write.extend(generate_with(parsed, options).drain(..));
+        to_write.push_str(SUFFIX);
+
+        Some(if let Some(replace_at) = RE_ENUM_TS_REGION.find(contents) {
+            let (start, end) = (replace_at.start(), replace_at.end());
+            (start, end, to_write)
+        } else {
+            // appended after a blank line, ending the file with a newline
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                to_write.insert(0, '\n');
+            }
+            to_write.push('\n');
+            (contents.len(), contents.len(), to_write)
+        })
+    }
+}
+
+/// The range of a generated region left behind after its enums were deleted, with the newline
+/// after it. Any `= Enum<` declaration which could not be parsed, like one nested in a block,
+/// keeps the region.
+fn remove_orphaned_region(contents: &str) -> Option<(usize, usize, String)> {
+    let region = RE_ENUM_TS_REGION.find(contents)?;
+    if RE_ENUM_DECLARATION.is_match(&contents[..region.start()])
+        || RE_ENUM_DECLARATION.is_match(&contents[region.end()..])
+    {
+        return None;
+    }
+    let (start, end) = region_with_newline(contents, region);
+    Some((start, end, String::new()))
+}
+
+/// The range of a generated region, with the newline after it so that removing it
+/// leaves the file as it was before the region was added
+fn region_with_newline(contents: &str, region: regex::Match) -> (usize, usize) {
+    if contents[region.end()..].starts_with('\n') {
+        (region.start(), region.end() + 1)
+    } else {
+        (region.start(), region.end())
+    }
+}
+
+/// Edits removing each generated region with the newline after it
+fn region_removals(contents: &str) -> Vec<(usize, usize, String)> {
+    RE_ENUM_TS_REGION
+        .find_iter(contents)
+        .map(|region| {
+            let (start, end) = region_with_newline(contents, region);
+            (start, end, String::new())
+        })
+        .collect()
+}
+
+pub fn make_edit(
+    contents: &str,
+    force: bool,
+    options: &Options,
+) -> Option<(Position, Position, String)> {
+    make_edit_parsed(contents, parse(contents), force, options)
+}
+
+/// Like [`make_edit`] for contents which were already parsed, like with [`parse_file`]
+pub fn make_edit_parsed(
+    contents: &str,
+    parsed: Parsed,
+    force: bool,
+    options: &Options,
+) -> Option<(Position, Position, String)> {
+    make_edit_offsets(contents, parsed, force, options).map(
+        |(start_offset, end_offset, to_insert)| {
+            let mut str_pos = StringPositions::new(contents);
+            // offsets at the end of the file have no character to take the position of
+            let mut get_pos =
+                |offset: usize| str_pos.get_pos(offset).unwrap_or_else(|| str_pos.get_eof());
+            (get_pos(start_offset), get_pos(end_offset), to_insert)
+        },
+    )
+}
 
-        Some(
-            if let Some(replace_at) = RE_ENUM_TS_REGION.find(&contents) {
-                let (start, end) = (replace_at.start(), replace_at.end());
-                (start, end, to_write)
+pub fn rewrite(contents: &str, force: bool, options: &Options) -> Option<String> {
+    rewrite_parsed(contents, parse(contents), force, options)
+}
+
+/// Like [`rewrite`] for contents which were already parsed, like with [`parse_file`]
+pub fn rewrite_parsed(
+    contents: &str,
+    parsed: Parsed,
+    force: bool,
+    options: &Options,
+) -> Option<String> {
+    make_edit_offsets(contents, parsed, force, options).map(
+        |(start_offset, end_offset, mut content)| {
+            let (before, _) = contents.split_at(start_offset);
+            let (_, after) = contents.split_at(end_offset);
+            let mut to_write = String::from(before);
+            to_write.extend(content.drain(..));
+            to_write.push_str(after);
+            to_write
+        },
+    )
+}
+
+/// A generated region or file which was out of date
+#[derive(Debug)]
+pub struct Change {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    /// The contents before the update, or `None` for a new file
+    pub existing: Option<String>,
+    /// The contents after the update, or empty for a deleted file
+    pub contents: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChangeKind {
+    /// Writes the generated code
+    Update,
+    /// Removes the generated region
+    RemoveRegion,
+    /// Deletes a generated `.enum.js` or `.enum.d.ts` file
+    Delete,
+}
+
+/// The number of enums declared in a source file and the updates to its generated code
+#[derive(Debug, Default)]
+pub struct FileUpdate {
+    pub enums: usize,
+    pub changes: Vec<Change>,
+    /// Whether enum variants were looked for in other files, so the update depends on them too
+    pub imports: bool,
+    /// Whether the file was skipped because the cache recorded it as up to date
+    pub cached: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Updates the generated region of the file at `path` which has been read into `raw_contents`
+pub fn rewrite_source(
+    path_ref: &Path,
+    raw_contents: String,
+    write: bool,
+    force: bool,
+    options: &Options,
+) -> io::Result<FileUpdate> {
+    if !needs_parse(&raw_contents) {
+        return Ok(FileUpdate::default());
+    }
+    let (style, file_contents) = TextStyle::detect(&raw_contents);
+    let (mut parsed, imports) = parse_file_imports(path_ref, &file_contents);
+    check_options(&mut parsed, options);
+    check_module(&file_contents, &mut parsed, options);
+    check_parsed(&parsed)?;
+    let warnings = parsed.warnings.clone();
+    let enums = parsed.enums.len();
+    let mut changes = Vec::new();
+    if let Some(edit) = make_edit_offsets(&file_contents, parsed, force, options) {
+        let substitution = style.splice(&raw_contents, &[edit]);
+        if write {
+            write_atomic(path_ref, &substitution)?;
+        }
+        changes.push(Change {
+            path: path_ref.to_path_buf(),
+            kind: if enums == 0 {
+                ChangeKind::RemoveRegion
             } else {
-                let end = contents.len() - 1;
-                (end, end, to_write)
+                ChangeKind::Update
             },
-        )
-    }
-}
-
-pub fn make_edit(contents: &str, force: bool) -> Option<(Position, Position, String)> {
-    make_edit_offsets(&contents, force).map(|(start_offset, end_offset, to_insert)| {
-        let mut str_pos = StringPositions::new(&contents);
-        (
-            str_pos
-                .get_pos(start_offset)
-                .expect("start replace has line column"),
-            str_pos
-                .get_pos(end_offset)
-                .expect("end replace has line column"),
-            to_insert,
-        )
+            existing: Some(raw_contents),
+            contents: substitution,
+        });
+    }
+    Ok(FileUpdate {
+        enums,
+        changes,
+        imports,
+        warnings,
+        ..FileUpdate::default()
+    })
+}
+
+/// Whether `contents` could declare an enum or have a generated region, checked before parsing
+/// as most files in a tree have neither
+pub fn needs_parse(contents: &str) -> bool {
+    RE_NEEDS_PARSE.is_match(contents)
+}
+
+/// The byte order mark and line endings of a file, kept when it is rewritten
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct TextStyle {
+    bom: bool,
+    crlf: bool,
+}
+
+impl TextStyle {
+    /// Detects the style from the first line ending, and returns the contents with `\n` line
+    /// endings and no byte order mark for parsing and generating
+    fn detect(raw: &str) -> (TextStyle, Cow<'_, str>) {
+        let (bom, contents) = match raw.strip_prefix('\u{feff}') {
+            Some(contents) => (true, contents),
+            None => (false, raw),
+        };
+        let crlf = contents
+            .find('\n')
+            .is_some_and(|idx| contents[..idx].ends_with('\r'));
+        let contents = if crlf {
+            Cow::Owned(contents.replace("\r\n", "\n"))
+        } else {
+            Cow::Borrowed(contents)
+        };
+        (TextStyle { bom, crlf }, contents)
+    }
+
+    fn apply(self, contents: &str) -> String {
+        let mut styled = String::with_capacity(contents.len() + 3);
+        if self.bom {
+            styled.push('\u{feff}');
+        }
+        if self.crlf {
+            styled.push_str(&contents.replace('\n', "\r\n"));
+        } else {
+            styled.push_str(contents);
+        }
+        styled
+    }
+
+    /// The offset in `raw` of an offset in the contents returned by [`TextStyle::detect`]
+    fn raw_offset(self, raw: &str, offset: usize) -> usize {
+        let bom = if self.bom { '\u{feff}'.len_utf8() } else { 0 };
+        if !self.crlf {
+            return bom + offset;
+        }
+        let bytes = raw.as_bytes();
+        let mut detected = 0;
+        for idx in bom..bytes.len() {
+            if detected == offset {
+                return idx;
+            }
+            // the `\r` of a `\r\n` was removed
+            if !(bytes[idx] == b'\r' && bytes.get(idx + 1) == Some(&b'\n')) {
+                detected += 1;
+            }
+        }
+        bytes.len()
+    }
+
+    /// Replaces ranges of the contents returned by [`TextStyle::detect`] in `raw`, converting only
+    /// the replacements to this style, so that lines outside of them keep their own endings
+    fn splice(self, raw: &str, edits: &[(usize, usize, String)]) -> String {
+        let replacement_style = TextStyle { bom: false, ..self };
+        let mut spliced = String::with_capacity(raw.len());
+        let mut last_end = 0;
+        for (start, end, replacement) in edits {
+            spliced.push_str(&raw[last_end..self.raw_offset(raw, *start)]);
+            spliced.push_str(&replacement_style.apply(replacement));
+            last_end = self.raw_offset(raw, *end);
+        }
+        spliced.push_str(&raw[last_end..]);
+        spliced
+    }
+}
+
+/// Writes to a temporary file next to `path` and renames it over `path`, so that an
+/// interrupted write can't leave a truncated file, keeping the permissions of the file
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    // replace the file a symlink points to rather than the symlink
+    let path = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(path)?,
+        _ => path.to_path_buf(),
+    };
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path =
+        path.with_file_name(format!(".{}.enum-ts-{}.tmp", file_name, std::process::id()));
+    let result = (|| {
+        let mut temp_file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        temp_file.write_all(contents.as_bytes())?;
+        if let Ok(metadata) = fs::metadata(&path) {
+            temp_file.set_permissions(metadata.permissions())?;
+        }
+        temp_file.sync_all()?;
+        fs::rename(&temp_path, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Fails for a file with enums which could not be parsed, rather than updating it without them
+fn check_parsed(parsed: &Parsed) -> io::Result<()> {
+    if parsed.errors.is_empty() {
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        parsed.errors.join("; "),
+    ))
+}
+
+/// Parses a file on disk, resolving `Enum<MsgVariants>` imported from a relative module
+pub fn parse_file(path: &Path, contents: &str) -> Parsed {
+    parse_file_imports(path, contents).0
+}
+
+/// Parses a file on disk, and whether any imported modules were looked for
+fn parse_file_imports(path: &Path, contents: &str) -> (Parsed, bool) {
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let imports = Cell::new(false);
+    let parsed = parse_with_imports(contents, |specifier| {
+        imports.set(true);
+        // "./msg" or "./msg.js" from ES modules, for "./msg.ts"
+        let specifier = specifier.strip_suffix(".js").unwrap_or(specifier);
+        ["", ".ts", ".tsx", ".d.ts", "/index.ts", "/index.tsx"]
+            .iter()
+            .map(|extension| dir.join(format!("{}{}", specifier, extension)))
+            .filter(|candidate| candidate.is_file())
+            .find_map(|candidate| fs::read_to_string(candidate).ok())
+    });
+    (parsed, imports.get())
+}
+
+/// `types.ts` generates into `types.enum.js` and `types.enum.d.ts`
+pub fn js_file_paths(path: &Path) -> (PathBuf, PathBuf) {
+    let file_name = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().to_string());
+    let stem = RE_TYPESCRIPT_EXTENSION.replace(&file_name, "");
+    (
+        path.with_file_name(format!("{}.enum.js", stem)),
+        path.with_file_name(format!("{}.enum.d.ts", stem)),
+    )
+}
+
+static RE_TYPESCRIPT_EXTENSION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\.[Dd])?\.[Tt][Ss][Xx]?$").unwrap());
+
+/// Generates JavaScript and declarations for the enums in `contents` as `(js, dts)`,
+/// or `None` if there are no enums or if `existing_js` was generated from the same enums.
+pub fn generate_js_files(
+    parsed: Parsed,
+    existing_js: Option<&str>,
+    force: bool,
+    options: &Options,
+) -> Option<(String, String)> {
+    // the files still have the code of enums whose variants were not found
+    if parsed.enums.is_empty() || !parsed.unresolved.is_empty() {
+        return None;
+    }
+    let header = String::from(GENERATED_FILE_PREFIX) + &hash_parsed(&parsed, options) + ">\n";
+    if !force && existing_js.is_some_and(|existing| existing.starts_with(&header)) {
+        return None;
+    }
+    let declarations = header.clone() + &generate_declarations(parsed.clone(), options) + "\n";
+    let js = header + &generate_js(parsed, options) + "\n";
+    Some((js, declarations))
+}
+
+/// Updates the `.enum.js` and `.enum.d.ts` files for the file at `path` which has been read
+/// into `raw_contents`
+pub fn write_js_source(
+    path_ref: &Path,
+    raw_contents: &str,
+    write: bool,
+    force: bool,
+    options: &Options,
+) -> io::Result<FileUpdate> {
+    let (js_path, dts_path) = js_file_paths(path_ref);
+    if !needs_parse(raw_contents) {
+        // files generated for enums which have since been deleted
+        return Ok(FileUpdate {
+            changes: delete_generated_files([js_path, dts_path], write)?,
+            ..FileUpdate::default()
+        });
+    }
+    let (style, file_contents) = TextStyle::detect(raw_contents);
+    // generated files follow the source's line endings, without a byte order mark
+    let style = TextStyle {
+        bom: false,
+        ..style
+    };
+    let existing_js = fs::read_to_string(&js_path).ok();
+    let (mut parsed, imports) = parse_file_imports(path_ref, &file_contents);
+    check_options(&mut parsed, options);
+    check_parsed(&parsed)?;
+    let warnings = parsed.warnings.clone();
+    let enums = parsed.enums.len();
+    if enums == 0 && parsed.unresolved.is_empty() {
+        return Ok(FileUpdate {
+            changes: delete_generated_files([js_path, dts_path], write)?,
+            imports,
+            warnings,
+            ..FileUpdate::default()
+        });
+    }
+    let mut changes = Vec::new();
+    if let Some((js, declarations)) = generate_js_files(
+        parsed,
+        existing_js
+            .as_deref()
+            .map(|existing| TextStyle::detect(existing).1)
+            .as_deref(),
+        force,
+        options,
+    ) {
+        let existing_dts = fs::read_to_string(&dts_path).ok();
+        for (path, existing, contents) in [
+            (js_path, existing_js, style.apply(&js)),
+            (dts_path, existing_dts, style.apply(&declarations)),
+        ] {
+            if write {
+                write_atomic(&path, &contents)?;
+            }
+            changes.push(Change {
+                path,
+                kind: ChangeKind::Update,
+                existing,
+                contents,
+            });
+        }
+    }
+    Ok(FileUpdate {
+        enums,
+        changes,
+        imports,
+        warnings,
+        ..FileUpdate::default()
     })
 }
 
-pub fn rewrite(contents: &str, force: bool) -> Option<String> {
-    make_edit_offsets(contents, force).map(|(start_offset, end_offset, mut content)| {
-        let (before, _) = contents.split_at(start_offset);
-        let (_, after) = contents.split_at(end_offset);
-        let mut to_write = String::from(before);
-        to_write.extend(content.drain(..));
-        to_write.push_str(after);
-        to_write
+/// Removes the generated regions from the file at `path`, writing it if `write` is set
+pub fn clean_file<P: AsRef<Path>>(path: P, write: bool) -> io::Result<FileUpdate> {
+    let path_ref = path.as_ref();
+    let raw_contents = fs::read_to_string(path_ref)?;
+    let (style, file_contents) = TextStyle::detect(&raw_contents);
+    let enums = parse_file(path_ref, &file_contents).enums.len();
+    let mut changes = Vec::new();
+    let regions = region_removals(&file_contents);
+    if !regions.is_empty() {
+        let cleaned = style.splice(&raw_contents, &regions);
+        if write {
+            write_atomic(path_ref, &cleaned)?;
+        }
+        changes.push(Change {
+            path: path_ref.to_path_buf(),
+            kind: ChangeKind::RemoveRegion,
+            existing: Some(raw_contents),
+            contents: cleaned,
+        });
+    }
+    Ok(FileUpdate {
+        enums,
+        changes,
+        ..FileUpdate::default()
     })
 }
 
-pub fn rewrite_file<P: AsRef<Path>>(path: P, write: bool, force: bool) -> bool {
+/// Deletes the `.enum.js` and `.enum.d.ts` files generated next to `path`, if `write` is set
+pub fn clean_js_files<P: AsRef<Path>>(path: P, write: bool) -> io::Result<FileUpdate> {
     let path_ref = path.as_ref();
-    let file_contents =
-        fs::read_to_string(&path_ref).expect("Something went wrong reading the file");
-    if let Some(substitution) = rewrite(&file_contents, force) {
+    let raw_contents = fs::read_to_string(path_ref)?;
+    let enums = parse_file(path_ref, &TextStyle::detect(&raw_contents).1)
+        .enums
+        .len();
+    let (js_path, dts_path) = js_file_paths(path_ref);
+    Ok(FileUpdate {
+        enums,
+        changes: delete_generated_files([js_path, dts_path], write)?,
+        ..FileUpdate::default()
+    })
+}
+
+/// Deletes the `.enum.js` and `.enum.d.ts` files at `paths` if `write` is set, leaving missing
+/// and handwritten files with the same names alone
+fn delete_generated_files(paths: [PathBuf; 2], write: bool) -> io::Result<Vec<Change>> {
+    let mut changes = Vec::new();
+    for path in paths {
+        let existing = match fs::read_to_string(&path) {
+            Ok(existing) => existing,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        if !TextStyle::detect(&existing)
+            .1
+            .starts_with(GENERATED_FILE_PREFIX)
+        {
+            continue;
+        }
         if write {
-            fs::write(&path, substitution).expect("Something went wrong writing the file");
-            println!("Wrote: {}", path_ref.to_string_lossy());
-        } else {
-            println!(
-                "Would write: {}\n<<enum-ts-dry-run>>\n{}\n<</enum-ts-dry-run>>",
-                &path_ref.to_string_lossy(),
-                &substitution,
+            fs::remove_file(&path)?;
+        }
+        changes.push(Change {
+            path,
+            kind: ChangeKind::Delete,
+            existing: Some(existing),
+            contents: String::new(),
+        });
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `contents` without any generated regions, or `None` if there are none
+    fn remove_regions(contents: &str) -> Option<String> {
+        let regions = region_removals(contents);
+        if regions.is_empty() {
+            return None;
+        }
+        Some(TextStyle::default().splice(contents, &regions))
+    }
+
+    const STOPLIGHT: &str = "type Stoplight = Enum<{\n    Green: 0;\n    Red: 0;\n}>;";
+
+    #[test]
+    fn append_region_at_end_of_file() {
+        let options = Options::default();
+        for source in [
+            format!("\n{}\n", STOPLIGHT),
+            format!("\n{}", STOPLIGHT),
+            format!("\n{}\n\n", STOPLIGHT),
+        ] {
+            let rewritten = rewrite(&source, false, &options).unwrap();
+            let (before, region) = rewritten.split_at(rewritten.find("//#region").unwrap());
+            assert_eq!(before.trim_end(), source.trim_end());
+            assert!(before.ends_with("\n\n"), "{:?}", rewritten);
+            assert!(region.ends_with("//#endregion\n"), "{:?}", rewritten);
+            assert_eq!(rewrite(&rewritten, false, &options), None);
+            assert_eq!(
+                rewrite(&rewritten, true, &options).as_ref(),
+                Some(&rewritten)
             );
         }
-        true
-    } else {
-        false
+        assert_eq!(rewrite("", false, &options), None);
+    }
+
+    #[test]
+    fn remove_region_without_enums() {
+        let options = Options::default();
+        let source = format!("\n{}\n", STOPLIGHT);
+        let generated = rewrite(&source, false, &options).unwrap();
+        let deleted = generated.replace(STOPLIGHT, "const stoplight = 1;");
+        assert_eq!(
+            rewrite(&deleted, false, &options).as_deref(),
+            Some("\nconst stoplight = 1;\n")
+        );
+        // only a declaration like `= Enum<` could be an enum which was not parsed
+        let mentions = generated.replace(STOPLIGHT, "// Enum<{}>\nlet a: MyEnum<T>;");
+        assert_eq!(
+            rewrite(&mentions, false, &options).as_deref(),
+            Some("\n// Enum<{}>\nlet a: MyEnum<T>;\n")
+        );
+        // an enum whose variants could not be found still owns the region
+        let unresolved = generated.replace(STOPLIGHT, "type Msg = Enum<MsgVariants>;");
+        assert_eq!(rewrite(&unresolved, false, &options), None);
+    }
+
+    #[test]
+    fn remove_all_regions() {
+        let options = Options::default();
+        let source = format!("\n{}\n", STOPLIGHT);
+        let generated = rewrite(&source, false, &options).unwrap();
+        assert_eq!(remove_regions(&generated).as_deref(), Some(source.as_str()));
+        let twice = format!("{}{}", generated, generated);
+        assert_eq!(remove_regions(&twice), Some(source.repeat(2)));
+        assert_eq!(remove_regions(&source), None);
+    }
+
+    #[test]
+    fn edit_at_end_of_file() {
+        let options = Options::default();
+        let source = format!("\n{}", STOPLIGHT);
+        let (start, end, to_insert) = make_edit(&source, false, &options).unwrap();
+        assert_eq!((start.line, start.col), (5, 3));
+        assert_eq!(start, end);
+        assert!(to_insert.starts_with("\n\n//#region enum-ts generated <"));
+
+        // a region ending the file without a newline
+        let rewritten = rewrite(&source, false, &options).unwrap();
+        let source = rewritten.trim_end();
+        let (_, end, _) = make_edit(source, true, &options).unwrap();
+        assert_eq!(end.line, source.lines().count());
+        assert_eq!(end.col, "//#endregion".len());
+    }
+
+    #[test]
+    fn skip_files_without_enums_or_regions() {
+        assert!(needs_parse(STOPLIGHT));
+        let rewritten = rewrite(&format!("\n{}", STOPLIGHT), false, &Options::default()).unwrap();
+        // a region left after its enums were removed
+        assert!(needs_parse(&rewritten.replace("Enum<{", "Other<{")));
+        assert!(!needs_parse("type Stoplight = Green | Red;\n"));
+        assert!(!needs_parse("// enum-ts generated <abc>\n"));
+    }
+
+    #[test]
+    fn warn_when_throw_makes_a_script_a_module() {
+        let warnings = |source: &str, options: &Options| {
+            let mut parsed = parse(source);
+            check_module(source, &mut parsed, options);
+            parsed.warnings
+        };
+        let script = format!("\n{}\n", STOPLIGHT);
+        assert!(warnings(&script, &Options::default()).is_empty());
+        let throw = Options {
+            unreachable: Unreachable::Throw("enum-ts-lib".to_string()),
+            ..Options::default()
+        };
+        assert_eq!(warnings(&script, &throw).len(), 1);
+        // the import generated in the region doesn't count as one of the file's own
+        let rewritten = rewrite(&script, false, &throw).unwrap();
+        assert_eq!(warnings(&rewritten, &throw).len(), 1);
+        let directive = format!("\n// enum-ts: unreachable=throw\n{}\n", STOPLIGHT);
+        assert_eq!(warnings(&directive, &Options::default()).len(), 1);
+        for module in [
+            format!("import {{ a }} from \"./a\";\n{}\n", STOPLIGHT),
+            format!("\nexport {}\n", STOPLIGHT),
+            format!("{}\nexport {{}};\n", script),
+        ] {
+            assert!(warnings(&module, &throw).is_empty(), "{:?}", module);
+        }
+        let dynamic = format!("const a = import(\"./a\");{}", script);
+        assert_eq!(warnings(&dynamic, &throw).len(), 1);
+    }
+
+    #[test]
+    fn text_style_round_trip() {
+        for raw in ["a\nb\n", "\u{feff}a\r\nb\r\n", "a\r\nb", "", "\u{feff}"] {
+            let (style, contents) = TextStyle::detect(raw);
+            assert!(!contents.contains('\r') && !contents.contains('\u{feff}'));
+            assert_eq!(style.apply(&contents), raw);
+        }
+        assert_eq!(
+            TextStyle::detect("\u{feff}a\r\n").0,
+            TextStyle {
+                bom: true,
+                crlf: true
+            }
+        );
+    }
+
+    #[test]
+    fn text_style_splice_keeps_other_line_endings() {
+        let raw = "\u{feff}a\r\nb\nc\r\nd\n";
+        let (style, contents) = TextStyle::detect(raw);
+        assert_eq!(contents, "a\nb\nc\nd\n");
+        // replaces "b\nc" and inserts at the end
+        let edits = [(2, 5, "x\ny".to_string()), (8, 8, "z\n".to_string())];
+        assert_eq!(style.splice(raw, &edits), "\u{feff}a\r\nx\r\ny\r\nd\nz\r\n");
+        let lf = "a\nb\r\n";
+        let (style, _) = TextStyle::detect(lf);
+        assert_eq!(style.splice(lf, &[(2, 3, "x\n".to_string())]), "a\nx\n\r\n");
     }
 }
@@ -1,11 +1,11 @@
 #!/bin/bash
 cargo build
-for FILE in $(ls ./tests)
+for FILE in $(ls ./tests | grep "\.ts$")
 do
     echo "Testing $FILE"
     echo ">>>>"
     echo "$(cat ./tests/$FILE)"
     echo "<<<<>>>>"
-    cat ./tests/$FILE | ./target/debug/enum-ts
+    cat ./tests/$FILE | ./target/debug/enum-ts generate
     echo "<<<<"
 done
\ No newline at end of file
@@ -8,7 +8,7 @@ export type BinaryTree<T> = Enum<{
   };
 }>;
 
-//#region enum-ts generated <40b84c8ed7eda02f>
+//#region enum-ts generated <4d0dd0befa976dbb>
 export type Leaf<T> = T;
 export type Branch<T> = {
   left: BinaryTree<T>;
@@ -0,0 +1,246 @@
+import { Enum } from "../enum";
+
+/** Every kind of variant and type parameter */
+// enum-ts: equals
+export type Shape<T extends object, E = Error> = Enum<{
+  /** A point on the plane */
+  Point: [x: number, y: number];
+  Tagged: [T, ...labels: string[]];
+  Empty: null;
+  Unknown: void;
+  "not-found": E;
+  "404": { path: string };
+  Nested: Shape<T, E>;
+}>;
+
+interface MsgVariants {
+  Ping: 0;
+  Text: string;
+}
+
+export type Msg = Enum<MsgVariants>;
+
+//#region enum-ts generated <9dca12edab1b2cd7>
+/** A point on the plane */
+export type Point = [x: number, y: number];
+export type Tagged<T extends object> = [T, ...labels: string[]];
+export type Empty = null;
+export type Unknown = void;
+export type NotFound<E = Error> = E;
+export type _404 = { path: string };
+export type Nested<T extends object, E = Error> = Shape<T, E>;
+/** A point on the plane */
+export function Point(...contents: Point): { Point: Point } {
+  return { Point: contents };
+}
+export function Tagged<T extends object>(...contents: Tagged<T>): { Tagged: Tagged<T> } {
+  return { Tagged: contents };
+}
+export function Empty(): { Empty: Empty } {
+  return { Empty: null };
+}
+export function Unknown(): { Unknown: Unknown } {
+  return { Unknown: undefined };
+}
+export function NotFound<E>(contents: NotFound<E>): { "not-found": NotFound<E> } {
+  return { "not-found": contents };
+}
+export function _404(contents: _404): { "404": _404 } {
+  return { "404": contents };
+}
+export function Nested<T extends object, E>(contents: Nested<T, E>): { Nested: Nested<T, E> } {
+  return { Nested: contents };
+}
+/** A point on the plane */
+export function isPoint<T extends object, E>(item: Shape<T, E>): item is { Point: Point } {
+  return item != null && "Point" in item;
+}
+export function isTagged<T extends object, E>(item: Shape<T, E>): item is { Tagged: Tagged<T> } {
+  return item != null && "Tagged" in item;
+}
+export function isEmpty<T extends object, E>(item: Shape<T, E>): item is { Empty: Empty } {
+  return item != null && "Empty" in item;
+}
+export function isUnknown<T extends object, E>(item: Shape<T, E>): item is { Unknown: Unknown } {
+  return item != null && "Unknown" in item;
+}
+export function isNotFound<T extends object, E>(item: Shape<T, E>): item is { "not-found": NotFound<E> } {
+  return item != null && "not-found" in item;
+}
+export function is_404<T extends object, E>(item: Shape<T, E>): item is { "404": _404 } {
+  return item != null && "404" in item;
+}
+export function isNested<T extends object, E>(item: Shape<T, E>): item is { Nested: Nested<T, E> } {
+  return item != null && "Nested" in item;
+}
+/** Every kind of variant and type parameter */
+export namespace Shape {
+  const unexpected = "Unexpected Enum variant for Shape<T, E>";
+  export function apply<T extends object, E, R>(fns: {
+    /** A point on the plane */
+    Point(...content: Point): R;
+    Tagged(...content: Tagged<T>): R;
+    Empty(): R;
+    Unknown(): R;
+    "not-found"(content: NotFound<E>): R;
+    "404"(content: _404): R;
+    Nested(content: Nested<T, E>): R;
+  }): (value: Shape<T, E>) => R {
+    return function matchShapeApply(item) {
+      return "Point" in item
+        ? fns.Point(...item.Point)
+        : "Tagged" in item
+        ? fns.Tagged(...item.Tagged)
+        : "Empty" in item
+        ? fns.Empty()
+        : "Unknown" in item
+        ? fns.Unknown()
+        : "not-found" in item
+        ? fns["not-found"](item["not-found"])
+        : "404" in item
+        ? fns["404"](item["404"])
+        : "Nested" in item
+        ? fns.Nested(item.Nested)
+        : (console.assert(false, unexpected, item) as never);
+    };
+  }
+  export function match<T extends object, E, R>(
+    value: Shape<T, E>,
+    fns: {
+      /** A point on the plane */
+      Point(...content: Point): R;
+      Tagged(...content: Tagged<T>): R;
+      Empty(): R;
+      Unknown(): R;
+      "not-found"(content: NotFound<E>): R;
+      "404"(content: _404): R;
+      Nested(content: Nested<T, E>): R;
+    }
+  ): R {
+    return apply(fns)(value);
+  }
+  export function equals<T extends object, E>(
+    a: Shape<T, E>,
+    b: Shape<T, E>,
+    eqFns: {
+      Point?(a: Point, b: Point): boolean;
+      Tagged?(a: Tagged<T>, b: Tagged<T>): boolean;
+      Empty?(a: Empty, b: Empty): boolean;
+      Unknown?(a: Unknown, b: Unknown): boolean;
+      "not-found"?(a: NotFound<E>, b: NotFound<E>): boolean;
+      "404"?(a: _404, b: _404): boolean;
+      Nested?(a: Nested<T, E>, b: Nested<T, E>): boolean;
+    } = {}
+  ): boolean {
+    if (a === b) return true;
+    if (a == null || b == null) return false;
+    return "Point" in a
+      ? "Point" in b && (eqFns.Point ? eqFns.Point(a.Point, b.Point) : enumTsDeepEqual(a.Point, b.Point))
+      : "Tagged" in a
+      ? "Tagged" in b && (eqFns.Tagged ? eqFns.Tagged(a.Tagged, b.Tagged) : enumTsDeepEqual(a.Tagged, b.Tagged))
+      : "Empty" in a
+      ? "Empty" in b && (eqFns.Empty ? eqFns.Empty(a.Empty, b.Empty) : enumTsDeepEqual(a.Empty, b.Empty))
+      : "Unknown" in a
+      ? "Unknown" in b && (eqFns.Unknown ? eqFns.Unknown(a.Unknown, b.Unknown) : enumTsDeepEqual(a.Unknown, b.Unknown))
+      : "not-found" in a
+      ? "not-found" in b && (eqFns["not-found"] ? eqFns["not-found"](a["not-found"], b["not-found"]) : enumTsDeepEqual(a["not-found"], b["not-found"]))
+      : "404" in a
+      ? "404" in b && (eqFns["404"] ? eqFns["404"](a["404"], b["404"]) : enumTsDeepEqual(a["404"], b["404"]))
+      : "Nested" in a
+      ? "Nested" in b && (eqFns.Nested ? eqFns.Nested(a.Nested, b.Nested) : Shape.equals(a.Nested, b.Nested))
+      : false;
+  }
+  export function hash<T extends object, E>(value: Shape<T, E>): number {
+    return "Point" in value
+      ? enumTsHashCombine(enumTsHashString("Point"), enumTsDeepHash(value.Point))
+      : "Tagged" in value
+      ? enumTsHashCombine(enumTsHashString("Tagged"), enumTsDeepHash(value.Tagged))
+      : "Empty" in value
+      ? enumTsHashCombine(enumTsHashString("Empty"), enumTsDeepHash(value.Empty))
+      : "Unknown" in value
+      ? enumTsHashCombine(enumTsHashString("Unknown"), enumTsDeepHash(value.Unknown))
+      : "not-found" in value
+      ? enumTsHashCombine(enumTsHashString("not-found"), enumTsDeepHash(value["not-found"]))
+      : "404" in value
+      ? enumTsHashCombine(enumTsHashString("404"), enumTsDeepHash(value["404"]))
+      : "Nested" in value
+      ? enumTsHashCombine(enumTsHashString("Nested"), Shape.hash(value.Nested))
+      : 0;
+  }
+}
+
+export type Ping = 0;
+export type Text = string;
+export function Ping(): { Ping: Ping } {
+  return { Ping: 0 };
+}
+export function Text(contents: Text): { Text: Text } {
+  return { Text: contents };
+}
+export function isPing(item: Msg): item is { Ping: Ping } {
+  return item != null && "Ping" in item;
+}
+export function isText(item: Msg): item is { Text: Text } {
+  return item != null && "Text" in item;
+}
+export namespace Msg {
+  const unexpected = "Unexpected Enum variant for Msg";
+  export function apply<R>(fns: {
+    Ping(): R;
+    Text(content: Text): R;
+  }): (value: Msg) => R {
+    return function matchMsgApply(item) {
+      return "Ping" in item
+        ? fns.Ping()
+        : "Text" in item
+        ? fns.Text(item.Text)
+        : (console.assert(false, unexpected, item) as never);
+    };
+  }
+  export function match<R>(
+    value: Msg,
+    fns: {
+      Ping(): R;
+      Text(content: Text): R;
+    }
+  ): R {
+    return apply(fns)(value);
+  }
+}
+
+function enumTsDeepEqual(a: unknown, b: unknown): boolean {
+  if (a === b || (a !== a && b !== b)) return true;
+  if (typeof a !== "object" || a === null) return false;
+  if (typeof b !== "object" || b === null) return false;
+  if (Array.isArray(a) !== Array.isArray(b)) return false;
+  const keys = Object.keys(a);
+  if (keys.length !== Object.keys(b).length) return false;
+  for (const key of keys) {
+    if (
+      !Object.prototype.hasOwnProperty.call(b, key) ||
+      !enumTsDeepEqual((a as any)[key], (b as any)[key])
+    )
+      return false;
+  }
+  return true;
+}
+function enumTsDeepHash(value: unknown): number {
+  if (typeof value !== "object" || value === null) {
+    return enumTsHashString(typeof value + String(value));
+  }
+  let hash = Array.isArray(value) ? 2 : 3;
+  for (const key of Object.keys(value).sort()) {
+    hash = enumTsHashCombine(hash, enumTsHashString(key));
+    hash = enumTsHashCombine(hash, enumTsDeepHash((value as any)[key]));
+  }
+  return hash;
+}
+function enumTsHashString(str: string): number {
+  let hash = 0;
+  for (let i = 0; i < str.length; i++) hash = enumTsHashCombine(hash, str.charCodeAt(i));
+  return hash;
+}
+function enumTsHashCombine(hash: number, next: number): number {
+  return (Math.imul(hash, 31) + next) | 0;
+}
+//#endregion
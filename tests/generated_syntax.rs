@@ -0,0 +1,117 @@
+//! Parses the output generated for every fixture in `tests/` to catch syntax errors,
+//! such as broken generics, which snapshots alone would not notice.
+
+use oxc_allocator::Allocator;
+use oxc_parser::Parser;
+use oxc_span::SourceType;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Option combinations which change the shape of the generated code
+const OPTION_SETS: &[&[&str]] = &[
+    &[],
+    &[
+        "--equals",
+        "--constants",
+        "--members=object",
+        "--dispatch=switch",
+        "--unreachable=throw",
+        "--creators=enum",
+        "--readonly",
+        "--freeze",
+    ],
+    &[
+        "--equals",
+        "--constants",
+        "--members=functions",
+        "--unreachable=handler:onUnexpected",
+        "--mangle=underscore",
+    ],
+];
+
+/// Variants named with reserved words, which can't name their type aliases and creators as is
+const RESERVED_WORD_VARIANTS: &str = r#"
+export type Command = Enum<{
+    "delete": { id: string };
+    "new": null;
+    class: string;
+    default: null;
+    "function": [name: string];
+    import: { from: string };
+}>;
+"#;
+
+#[test]
+fn generated_output_parses() {
+    let mut failures = Vec::new();
+    let mut checked = 0;
+    let mut fixtures = vec![(
+        "RESERVED_WORD_VARIANTS".to_string(),
+        RESERVED_WORD_VARIANTS.to_string(),
+    )];
+    for entry in fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension() != Some(OsStr::new("ts")) {
+            continue;
+        }
+        fixtures.push((
+            path.display().to_string(),
+            fs::read_to_string(&path).unwrap(),
+        ));
+    }
+    for (name, source) in fixtures.iter() {
+        for options in OPTION_SETS {
+            for (mode, source_type) in [
+                (&["pipe", "--full"], SourceType::ts()),
+                (&["generate", "--target=js"], SourceType::mjs()),
+                (&["generate", "--target=dts"], SourceType::d_ts()),
+            ] {
+                let generated = generate(source, mode, options);
+                let allocator = Allocator::default();
+                let parsed = Parser::new(&allocator, &generated, source_type).parse();
+                checked += 1;
+                if parsed.panicked || !parsed.errors.is_empty() {
+                    failures.push(format!(
+                        "{} {} {}:\n{:?}\n{}",
+                        name,
+                        mode.join(" "),
+                        options.join(" "),
+                        parsed.errors,
+                        generated,
+                    ));
+                }
+            }
+        }
+    }
+    assert!(checked > 0, "no fixtures found");
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
+
+fn generate(source: &str, mode: &[&str], options: &[&str]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_enum-ts"))
+        .args(mode)
+        .args(options)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "enum-ts {} {} failed: {}",
+        mode.join(" "),
+        options.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
@@ -18,7 +18,7 @@ export type CompletionDropdownItemView = Enum<{
   };
 }>;
 
-//#region enum-ts generated <2c652eb64b89014>
+//#region enum-ts generated <3eb8f98070ef15b0>
 export type NavHintView = {
   /** Documentation comment */
   text: string;
@@ -5,26 +5,26 @@ export type Result<O, E> = Enum<{
   Err: E;
 }>;
 
-//#region enum-ts generated <670abf0a9f424d5a>
-export type Ok<O, E> = O;
-export type Err<O, E> = E;
-export function Ok<O, E>(contents: Ok<O, E>): { Ok: Ok<O, E> } {
+//#region enum-ts generated <689ee1e38f62ca85>
+export type Ok<O> = O;
+export type Err<E> = E;
+export function Ok<O>(contents: Ok<O>): { Ok: Ok<O> } {
   return { Ok: contents };
 }
-export function Err<O, E>(contents: Err<O, E>): { Err: Err<O, E> } {
+export function Err<E>(contents: Err<E>): { Err: Err<E> } {
   return { Err: contents };
 }
-export function isOk<O, E>(item: Result<O, E>): item is { Ok: Ok<O, E> } {
+export function isOk<O, E>(item: Result<O, E>): item is { Ok: Ok<O> } {
   return item != null && "Ok" in item;
 }
-export function isErr<O, E>(item: Result<O, E>): item is { Err: Err<O, E> } {
+export function isErr<O, E>(item: Result<O, E>): item is { Err: Err<E> } {
   return item != null && "Err" in item;
 }
 export namespace Result {
   const unexpected = "Unexpected Enum variant for Result<O, E>";
   export function apply<O, E, R>(fns: {
-    Ok(content: Ok<O, E>): R;
-    Err(content: Err<O, E>): R;
+    Ok(content: Ok<O>): R;
+    Err(content: Err<E>): R;
   }): (value: Result<O, E>) => R {
     return function matchResultApply(item) {
       return "Ok" in item
@@ -37,8 +37,8 @@ export namespace Result {
   export function match<O, E, R>(
     value: Result<O, E>,
     fns: {
-      Ok(content: Ok<O, E>): R;
-      Err(content: Err<O, E>): R;
+      Ok(content: Ok<O>): R;
+      Err(content: Err<E>): R;
     }
   ): R {
     return apply(fns)(value);
@@ -6,18 +6,18 @@ export type Stoplight = Enum<{
   Red: 0;
 }>;
 
-//#region enum-ts generated <193cc55841409219>
+//#region enum-ts generated <78fd47de1693bbab>
 export type Green = 0;
 export type Yellow = 0;
 export type Red = 0;
-export function Green(contents: Green): { Green: Green } {
-  return { Green: contents };
+export function Green(): { Green: Green } {
+  return { Green: 0 };
 }
-export function Yellow(contents: Yellow): { Yellow: Yellow } {
-  return { Yellow: contents };
+export function Yellow(): { Yellow: Yellow } {
+  return { Yellow: 0 };
 }
-export function Red(contents: Red): { Red: Red } {
-  return { Red: contents };
+export function Red(): { Red: Red } {
+  return { Red: 0 };
 }
 export function isGreen(item: Stoplight): item is { Green: Green } {
   return item != null && "Green" in item;
@@ -31,26 +31,26 @@ export function isRed(item: Stoplight): item is { Red: Red } {
 export namespace Stoplight {
   const unexpected = "Unexpected Enum variant for Stoplight";
   export function apply<R>(fns: {
-    Green(content: Green): R;
-    Yellow(content: Yellow): R;
-    Red(content: Red): R;
+    Green(): R;
+    Yellow(): R;
+    Red(): R;
   }): (value: Stoplight) => R {
     return function matchStoplightApply(item) {
       return "Green" in item
-        ? fns.Green(item.Green)
+        ? fns.Green()
         : "Yellow" in item
-        ? fns.Yellow(item.Yellow)
+        ? fns.Yellow()
         : "Red" in item
-        ? fns.Red(item.Red)
+        ? fns.Red()
         : (console.assert(false, unexpected, item) as never);
     };
   }
   export function match<R>(
     value: Stoplight,
     fns: {
-      Green(content: Green): R;
-      Yellow(content: Yellow): R;
-      Red(content: Red): R;
+      Green(): R;
+      Yellow(): R;
+      Red(): R;
     }
   ): R {
     return apply(fns)(value);
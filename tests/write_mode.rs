@@ -0,0 +1,337 @@
+//! Runs `enum-ts write` and friends over scratch directories
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::{env, fs, process};
+
+const STOPLIGHT: &str =
+    "// stoplight\nexport type Stoplight = Enum<{\n    Green: 0;\n    Red: 0;\n}>;\n";
+
+/// An empty directory unique to the test, outside of the repository so that its
+/// `.gitignore` does not apply
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("enum-ts-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn enum_ts(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_enum-ts"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn write_continues_past_unreadable_files() {
+    let dir = scratch_dir("write_continues_past_unreadable_files");
+    fs::write(dir.join("a.ts"), STOPLIGHT).unwrap();
+    fs::write(dir.join("b.ts"), b"type Bad = \xff;\n").unwrap();
+    fs::write(dir.join("c.ts"), STOPLIGHT).unwrap();
+
+    let output = enum_ts(&dir, &["write", ".", "missing"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    assert!(
+        stderr.contains("b.ts: stream did not contain valid UTF-8"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("missing"), "{}", stderr);
+    assert!(
+        stderr.contains("Scanned 4 files with 2 enums in "),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains(": 2 updated, 0 up to date, 2 errors"),
+        "{}",
+        stderr
+    );
+    for name in ["a.ts", "c.ts"] {
+        let written = fs::read_to_string(dir.join(name)).unwrap();
+        assert!(written.contains("//#region enum-ts generated"), "{}", name);
+    }
+}
+
+#[test]
+fn check_continues_past_enums_which_cannot_be_parsed() {
+    let dir = scratch_dir("check_continues_past_enums_which_cannot_be_parsed");
+    // variants which are not indented are still parsed
+    fs::write(dir.join("a.ts"), "\ntype M = Enum<{\nA: string;\n}>;\n").unwrap();
+    fs::write(
+        dir.join("b.ts"),
+        "\ntype M = Enum<{\n    A: string;\n  B: number;\n}>;\n",
+    )
+    .unwrap();
+    fs::write(dir.join("c.ts"), STOPLIGHT).unwrap();
+
+    let output = enum_ts(&dir, &["check", "--no-cache"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr);
+    assert!(
+        stderr.contains("b.ts: enum M: line indentation is irregular: `B: number;`"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains(": 2 out of date, 0 up to date, 1 error"),
+        "{}",
+        stderr
+    );
+
+    assert_eq!(enum_ts(&dir, &["write"]).status.code(), Some(2));
+    let written = fs::read_to_string(dir.join("a.ts")).unwrap();
+    assert!(
+        written.contains("    export function apply<R>("),
+        "{}",
+        written
+    );
+    assert!(!fs::read_to_string(dir.join("b.ts"))
+        .unwrap()
+        .contains("//#region"));
+}
+
+#[test]
+fn check_exits_with_1_when_out_of_date() {
+    let dir = scratch_dir("check_exits_with_1_when_out_of_date");
+    fs::write(dir.join("a.ts"), STOPLIGHT).unwrap();
+
+    assert_eq!(enum_ts(&dir, &["check"]).status.code(), Some(1));
+    assert_eq!(enum_ts(&dir, &["write"]).status.code(), Some(0));
+    assert_eq!(enum_ts(&dir, &["check"]).status.code(), Some(0));
+}
+
+#[test]
+fn write_keeps_line_endings_byte_order_mark_and_mode() {
+    let dir = scratch_dir("write_keeps_line_endings_byte_order_mark_and_mode");
+    let path = dir.join("a.ts");
+    fs::write(
+        &path,
+        format!("\u{feff}{}", STOPLIGHT.replace('\n', "\r\n")),
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    }
+
+    assert_eq!(enum_ts(&dir, &["write"]).status.code(), Some(0));
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.starts_with('\u{feff}'));
+    assert!(written.contains("//#region enum-ts generated"));
+    assert_eq!(
+        written.matches('\n').count(),
+        written.matches("\r\n").count()
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+    // no temporary files are left behind
+    let mut names: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    names.sort();
+    assert_eq!(names, [".enum-ts-cache", "a.ts"]);
+    // and the styled file is up to date
+    assert_eq!(enum_ts(&dir, &["check"]).status.code(), Some(0));
+
+    assert_eq!(enum_ts(&dir, &["write", "--js"]).status.code(), Some(0));
+    let js = fs::read_to_string(dir.join("a.enum.js")).unwrap();
+    assert!(!js.starts_with('\u{feff}'));
+    assert_eq!(js.matches('\n').count(), js.matches("\r\n").count());
+    assert_eq!(enum_ts(&dir, &["check", "--js"]).status.code(), Some(0));
+}
+
+#[test]
+fn json_format_reports_each_file() {
+    let dir = scratch_dir("json_format_reports_each_file");
+    fs::write(dir.join("a.ts"), STOPLIGHT).unwrap();
+    fs::write(dir.join("b.ts"), "export const b = 1;\n").unwrap();
+
+    let output = enum_ts(&dir, &["check", "--format", "json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut files = report["files"].as_array().unwrap().clone();
+    files.sort_by_key(|file| file["path"].as_str().unwrap().to_string());
+    assert_eq!(files[0]["status"], "out-of-date");
+    assert_eq!(files[0]["enums"], 1);
+    assert_eq!(files[1]["status"], "up-to-date");
+    assert_eq!(files[1]["enums"], 0);
+    let summary = &report["summary"];
+    assert_eq!(summary["scanned"], 2);
+    assert_eq!(summary["out_of_date"], 1);
+    assert_eq!(summary["up_to_date"], 1);
+    assert_eq!(summary["errors"], 0);
+}
+
+#[test]
+fn dry_run_output_is_sorted() {
+    let dir = scratch_dir("dry_run_output_is_sorted");
+    let mut names: Vec<String> = (0..20).map(|idx| format!("{:02}.ts", idx)).collect();
+    for name in names.iter() {
+        fs::write(dir.join(name), STOPLIGHT).unwrap();
+    }
+
+    let output = enum_ts(&dir, &["write", "--dry-run", "--jobs", "4"]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let written: Vec<String> = stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("Would write: "))
+        .map(|path| {
+            Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    names.sort();
+    assert_eq!(written, names);
+    let again = enum_ts(&dir, &["write", "--dry-run", "--jobs", "4"]);
+    assert_eq!(String::from_utf8(again.stdout).unwrap(), stdout);
+}
+
+#[test]
+fn check_reports_regions_without_enums() {
+    let dir = scratch_dir("check_reports_regions_without_enums");
+    let path = dir.join("a.ts");
+    fs::write(&path, STOPLIGHT).unwrap();
+    assert_eq!(enum_ts(&dir, &["write"]).status.code(), Some(0));
+    let written = fs::read_to_string(&path).unwrap();
+    fs::write(&path, written.replace("Enum<{", "Other<{")).unwrap();
+
+    let output = enum_ts(&dir, &["check"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Region without enums: "), "{}", stdout);
+
+    assert_eq!(enum_ts(&dir, &["write"]).status.code(), Some(0));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        STOPLIGHT.replace("Enum<{", "Other<{")
+    );
+}
+
+#[test]
+fn write_js_deletes_files_of_deleted_enums() {
+    let dir = scratch_dir("write_js_deletes_files_of_deleted_enums");
+    fs::write(dir.join("a.ts"), STOPLIGHT).unwrap();
+    // handwritten, so not deleted
+    fs::write(dir.join("b.enum.js"), "export {};\n").unwrap();
+    fs::write(dir.join("b.ts"), "export {};\n").unwrap();
+    assert_eq!(enum_ts(&dir, &["write", "--js"]).status.code(), Some(0));
+    assert!(dir.join("a.enum.js").exists());
+
+    fs::write(dir.join("a.ts"), "export const stoplight = 1;\n").unwrap();
+    let check = enum_ts(&dir, &["check", "--js"]);
+    assert_eq!(check.status.code(), Some(1));
+    let stdout = String::from_utf8(check.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 2, "{}", stdout);
+    assert_eq!(enum_ts(&dir, &["write", "--js"]).status.code(), Some(0));
+    assert!(!dir.join("a.enum.js").exists());
+    assert!(!dir.join("a.enum.d.ts").exists());
+    assert!(dir.join("b.enum.js").exists());
+}
+
+#[test]
+fn clean_removes_regions_and_generated_files() {
+    let dir = scratch_dir("clean_removes_regions_and_generated_files");
+    let path = dir.join("a.ts");
+    fs::write(&path, STOPLIGHT).unwrap();
+    // handwritten, so not removed
+    fs::write(dir.join("b.enum.js"), "export {};\n").unwrap();
+    fs::write(dir.join("b.ts"), STOPLIGHT).unwrap();
+    assert_eq!(enum_ts(&dir, &["write", "a.ts"]).status.code(), Some(0));
+    assert_eq!(
+        enum_ts(&dir, &["write", "--js", "a.ts"]).status.code(),
+        Some(0)
+    );
+    assert!(dir.join("a.enum.js").exists());
+
+    let dry_run = enum_ts(&dir, &["clean", "--dry-run"]);
+    let stdout = String::from_utf8(dry_run.stdout).unwrap();
+    assert!(stdout.starts_with("Would remove region: "), "{}", stdout);
+    assert!(fs::read_to_string(&path).unwrap().contains("//#region"));
+
+    let output = enum_ts(&dir, &["clean"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stderr.contains(": 1 cleaned, "), "{}", stderr);
+    assert_eq!(fs::read_to_string(&path).unwrap(), STOPLIGHT);
+
+    let output = enum_ts(&dir, &["clean", "--js"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("Deleted: ").count(), 2, "{}", stdout);
+    assert!(!dir.join("a.enum.js").exists());
+    assert!(!dir.join("a.enum.d.ts").exists());
+    assert!(dir.join("b.enum.js").exists());
+}
+
+#[test]
+fn unchanged_files_are_skipped() {
+    let dir = scratch_dir("unchanged_files_are_skipped");
+    fs::write(dir.join("a.ts"), STOPLIGHT).unwrap();
+    fs::write(dir.join("b.ts"), "export const b = 1;\n").unwrap();
+    assert_eq!(enum_ts(&dir, &["write"]).status.code(), Some(0));
+    assert!(dir.join(".enum-ts-cache").exists());
+
+    let cached = |args: &[&str]| {
+        let output = enum_ts(&dir, args);
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.code(), report["summary"]["cached"].clone())
+    };
+    assert_eq!(cached(&["check", "--format=json"]), (Some(0), 2.into()));
+    assert_eq!(
+        cached(&["check", "--format=json", "--no-cache"]),
+        (Some(0), 0.into())
+    );
+    // other options generate different code, so nothing is skipped
+    assert_eq!(
+        cached(&["check", "--format=json", "--equals"]),
+        (Some(1), 0.into())
+    );
+
+    let written = fs::read_to_string(dir.join("a.ts")).unwrap();
+    fs::write(
+        dir.join("a.ts"),
+        written.replace("Red: 0;", "Red: 0;\n    Yellow: 0;"),
+    )
+    .unwrap();
+    assert_eq!(cached(&["check", "--format=json"]), (Some(1), 1.into()));
+
+    fs::create_dir(dir.join("node_modules")).unwrap();
+    assert_eq!(enum_ts(&dir, &["write"]).status.code(), Some(0));
+    assert!(dir.join("node_modules/.cache/enum-ts/cache.json").exists());
+}
+
+#[test]
+fn checks_leave_the_directory_unchanged() {
+    let dir = scratch_dir("checks_leave_the_directory_unchanged");
+    fs::write(dir.join("a.ts"), STOPLIGHT).unwrap();
+    let listing = || {
+        let mut names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        names
+    };
+    let before = listing();
+    assert_eq!(enum_ts(&dir, &["check"]).status.code(), Some(1));
+    assert_eq!(enum_ts(&dir, &["diff"]).status.code(), Some(1));
+    assert_eq!(
+        enum_ts(&dir, &["write", "--dry-run"]).status.code(),
+        Some(0)
+    );
+    assert_eq!(listing(), before);
+    assert_eq!(fs::read_to_string(dir.join("a.ts")).unwrap(), STOPLIGHT);
+}
@@ -171,30 +171,33 @@ function editFormat(
   replacement: string;
 } | null> {
   const versionAtStart = doc.version;
-  return execa
-    .command(`${options.bin} --edit-l1c0`, {
-      stdin: "pipe",
-      input: doc.getText(),
-    })
-    .then((done) => {
-      const match = HAS_EDIT_RE.exec(done.stderr);
-      if (match) {
-        const startLine = parseInt(match[1]) - 1;
-        const startCharacter = parseInt(match[2]);
-        const endLine = parseInt(match[3]) - 1;
-        const endCharacter = parseInt(match[4]);
-        return {
-          range: new vscode.Range(
-            startLine,
-            startCharacter,
-            endLine,
-            endCharacter
-          ),
-          version: versionAtStart,
-          replacement: done.stdout,
-        };
-      } else {
-        return null;
-      }
-    });
+  const args = ["--edit-l1c0"];
+  // so that variants imported from relative modules are found
+  if (doc.uri.scheme === "file") {
+    args.push("--stdin-path", doc.fileName);
+  }
+  return execa(options.bin, args, {
+    stdin: "pipe",
+    input: doc.getText(),
+  }).then((done) => {
+    const match = HAS_EDIT_RE.exec(done.stderr);
+    if (match) {
+      const startLine = parseInt(match[1]) - 1;
+      const startCharacter = parseInt(match[2]);
+      const endLine = parseInt(match[3]) - 1;
+      const endCharacter = parseInt(match[4]);
+      return {
+        range: new vscode.Range(
+          startLine,
+          startCharacter,
+          endLine,
+          endCharacter
+        ),
+        version: versionAtStart,
+        replacement: done.stdout,
+      };
+    } else {
+      return null;
+    }
+  });
 }